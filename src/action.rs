@@ -0,0 +1,222 @@
+//! The registry of actions the app can perform from the sidebar, the menu
+//! bar, the command palette and the keyboard, and the keys bound to them.
+//! Bindings are read from `AppConfig`; actions missing there keep their
+//! defaults.
+
+use crate::{app::ViewMode, category::Category, export::ExportFormat};
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    SelectNext,
+    SelectPrevious,
+    OpenSelected,
+    OpenComments,
+    ToggleFavorite,
+    Refresh,
+    ShowCategory(Category),
+    ShowView(ViewMode),
+    ToggleTheme,
+    ToggleHideRead,
+    MarkAllRead,
+    /// Reveals or hides again the stories muted by the kill file.
+    ToggleMuted,
+    FocusSearch,
+    Export(ExportFormat),
+    Import,
+    /// Opens any item's discussion by id. Offered by the command palette
+    /// when an id is typed; not part of `ALL`.
+    OpenItem(u64),
+    CommandPalette,
+    ToggleSettings,
+    ToggleHelp,
+}
+
+impl Action {
+    pub const ALL: &[Action] = &[
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::OpenSelected,
+        Action::OpenComments,
+        Action::ToggleFavorite,
+        Action::Refresh,
+        Action::ShowCategory(Category::Top),
+        Action::ShowCategory(Category::New),
+        Action::ShowCategory(Category::Best),
+        Action::ShowCategory(Category::Ask),
+        Action::ShowCategory(Category::Show),
+        Action::ShowCategory(Category::Jobs),
+        Action::ShowView(ViewMode::Fetched),
+        Action::ShowView(ViewMode::Saved),
+        Action::ShowView(ViewMode::Search),
+        Action::ShowView(ViewMode::Watching),
+        Action::ToggleTheme,
+        Action::ToggleHideRead,
+        Action::MarkAllRead,
+        Action::ToggleMuted,
+        Action::FocusSearch,
+        Action::Export(ExportFormat::Json),
+        Action::Export(ExportFormat::Csv),
+        Action::Export(ExportFormat::Markdown),
+        Action::Export(ExportFormat::Html),
+        Action::Import,
+        Action::CommandPalette,
+        Action::ToggleSettings,
+        Action::ToggleHelp,
+    ];
+
+    /// The name used for the action in `config.json`.
+    pub fn name(&self) -> String {
+        match self {
+            Action::SelectNext => "select_next".to_string(),
+            Action::SelectPrevious => "select_previous".to_string(),
+            Action::OpenSelected => "open".to_string(),
+            Action::OpenComments => "open_comments".to_string(),
+            Action::ToggleFavorite => "toggle_favorite".to_string(),
+            Action::Refresh => "refresh".to_string(),
+            Action::ShowCategory(category) => {
+                format!("show_{}", category.display_name().to_lowercase())
+            }
+            Action::ShowView(ViewMode::Fetched) => "view_fetched".to_string(),
+            Action::ShowView(ViewMode::Saved) => "view_saved".to_string(),
+            Action::ShowView(ViewMode::Search) => "view_search".to_string(),
+            Action::ShowView(ViewMode::Watching) => "view_watching".to_string(),
+            Action::ToggleTheme => "toggle_theme".to_string(),
+            Action::ToggleHideRead => "toggle_hide_read".to_string(),
+            Action::MarkAllRead => "mark_all_read".to_string(),
+            Action::ToggleMuted => "toggle_muted".to_string(),
+            Action::FocusSearch => "focus_search".to_string(),
+            Action::Export(format) => format!("export_{}", format.extension()),
+            Action::Import => "import".to_string(),
+            Action::OpenItem(_) => "open_item".to_string(),
+            Action::CommandPalette => "command_palette".to_string(),
+            Action::ToggleSettings => "toggle_settings".to_string(),
+            Action::ToggleHelp => "toggle_help".to_string(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Action::SelectNext => "Select next story".to_string(),
+            Action::SelectPrevious => "Select previous story".to_string(),
+            Action::OpenSelected => "Open selected story".to_string(),
+            Action::OpenComments => "Open comments".to_string(),
+            Action::ToggleFavorite => "Star / unstar".to_string(),
+            Action::Refresh => "Refresh".to_string(),
+            Action::ShowCategory(category) => format!("Show {} stories", category.display_name()),
+            Action::ShowView(ViewMode::Fetched) => "Go to fetched stories".to_string(),
+            Action::ShowView(ViewMode::Saved) => "Go to saved favorites".to_string(),
+            Action::ShowView(ViewMode::Search) => "Go to search results".to_string(),
+            Action::ShowView(ViewMode::Watching) => "Go to watched authors and domains".to_string(),
+            Action::ToggleTheme => "Toggle dark / light theme".to_string(),
+            Action::ToggleHideRead => "Toggle hiding read stories".to_string(),
+            Action::MarkAllRead => "Mark all as read".to_string(),
+            Action::ToggleMuted => "Show / hide muted stories".to_string(),
+            Action::FocusSearch => "Focus search".to_string(),
+            Action::Export(format) => format!("Export favorites as {}", format.display_name()),
+            Action::Import => "Import favorites…".to_string(),
+            Action::OpenItem(id) => format!("Open item #{}", id),
+            Action::CommandPalette => "Command palette".to_string(),
+            Action::ToggleSettings => "Settings…".to_string(),
+            Action::ToggleHelp => "Show key bindings".to_string(),
+        }
+    }
+
+    /// Whether the command palette lists the action. Moving the selection
+    /// makes no sense from there.
+    pub fn in_palette(&self) -> bool {
+        !matches!(
+            self,
+            Action::SelectNext | Action::SelectPrevious | Action::CommandPalette
+        )
+    }
+
+    fn default_keys(&self) -> Vec<String> {
+        self.default_key_names()
+            .iter()
+            .map(|k| k.to_string())
+            .collect()
+    }
+
+    fn default_key_names(&self) -> &'static [&'static str] {
+        match self {
+            Action::SelectNext => &["J", "Down"],
+            Action::SelectPrevious => &["K", "Up"],
+            Action::OpenSelected => &["Enter"],
+            Action::OpenComments => &["C"],
+            Action::ToggleFavorite => &["S"],
+            Action::Refresh => &["R"],
+            Action::ShowCategory(Category::Top) => &["1"],
+            Action::ShowCategory(Category::New) => &["2"],
+            Action::ShowCategory(Category::Best) => &["3"],
+            Action::ShowCategory(Category::Ask) => &["4"],
+            Action::ShowCategory(Category::Show) => &["5"],
+            Action::ShowCategory(Category::Jobs) => &["6"],
+            Action::FocusSearch => &["/"],
+            Action::CommandPalette => &["Ctrl+K", "Ctrl+P"],
+            Action::ToggleHelp => &["?"],
+            _ => &[],
+        }
+    }
+}
+
+/// Keys bound to each action by name, e.g. `"select_next": ["J", "Down"]`.
+/// A key is an egui key name, optionally prefixed with `Ctrl+`, `Shift+`,
+/// `Alt+` or `Cmd+`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyBindings(BTreeMap<String, Vec<String>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .iter()
+                .map(|action| (action.name(), action.default_keys()))
+                .collect(),
+        )
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        self.0
+            .get(&action.name())
+            .cloned()
+            .unwrap_or_else(|| action.default_keys())
+    }
+
+    /// The parsed shortcuts of an action; keys that do not parse are skipped.
+    pub fn shortcuts(&self, action: Action) -> Vec<KeyboardShortcut> {
+        self.keys(action)
+            .iter()
+            .filter_map(|key| parse_shortcut(key))
+            .collect()
+    }
+}
+
+/// Parses a key such as `J`, `Down`, `/` or `Ctrl+K`.
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let text = text.trim();
+    // A lone "+" is the plus key, not a separator.
+    let (prefix, key) = match text.rsplit_once('+') {
+        Some((prefix, "")) => (prefix.strip_suffix('+').unwrap_or(prefix), "+"),
+        Some((prefix, key)) => (prefix, key),
+        None => ("", text),
+    };
+
+    let mut modifiers = Modifiers::NONE;
+    for modifier in prefix.split('+').filter(|m| !m.is_empty()) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Modifiers::CTRL,
+            "shift" => Modifiers::SHIFT,
+            "alt" | "option" => Modifiers::ALT,
+            "cmd" | "command" => Modifiers::COMMAND,
+            _ => return None,
+        };
+    }
+
+    Some(KeyboardShortcut::new(modifiers, Key::from_name(key)?))
+}
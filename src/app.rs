@@ -1,21 +1,173 @@
 use crate::{
+    action::{Action, KeyBindings},
+    article::{fetch_article, fetch_image, Article},
     category::Category,
-    hn_api::{create_client, fetch_category},
-    story::{Story, StoryDisplay},
-    storage::FavoritesDB,
-    config::Config,
+    comment::CommentThread,
+    export::{self, ExportFormat},
+    hn_api::{fetch_category, fetch_comment_thread, fetch_item, fetch_items, fetch_user, ApiError, HnClient, PAGE_SIZE},
+    html::{self, Block},
+    import::{self, ImportPreview},
+    item::{Item, ItemKind},
+    listing::ListOptions,
+    mute::{KillFile, MuteRule},
+    palette::{self, Command},
+    search::{search, SearchQuery},
+    story::{unix_now, Story, StoryDisplay},
+    storage::{FavoriteMatch, FavoritesDB, Folder, HistoryPoint, Visit},
+    config::{AppConfig, Config},
     theme::{GruvboxTheme, apply_theme},
     ui,
+    user::User,
+    watch::{WatchKind, WatchList},
 };
 use eframe::egui;
-use reqwest::Client;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cached items older than this are dropped on startup.
+const CACHE_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Score and rank history older than this is dropped on startup.
+const HISTORY_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Edited notes are written back once typing pauses for this long.
+const NOTES_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Items fetched in the background, waiting to be picked up by the UI thread.
+struct PendingPage {
+    category: Category,
+    /// The `list_generation` a load-more page was requested for; first
+    /// pages replace the list and ignore it.
+    generation: u64,
+    /// The full id list, present when this is the first page of a fresh fetch.
+    ids: Option<Vec<u64>>,
+    /// The ids this page covers, in ranking order.
+    page_ids: Vec<u64>,
+    items: Vec<Item>,
+}
+
+type SearchOutcome = Result<Vec<StoryDisplay>, ApiError>;
+
+/// First pages fetched for the Watching view, one per category.
+type WatchOutcome = Result<PendingPage, ApiError>;
+
+struct PendingComments {
+    story_id: u64,
+    result: Result<CommentThread, ApiError>,
+}
+
+/// Steps of the import dialog: pick a file, wait for ids to resolve,
+/// review the preview, then see the outcome.
+pub enum ImportStage {
+    Choosing,
+    Resolving,
+    Preview(ImportPreview),
+    Done(usize),
+    Failed(String),
+}
+
+pub struct ImportDialog {
+    pub path: String,
+    pub stage: ImportStage,
+}
+
+struct PendingArticle {
+    story_id: u64,
+    result: Result<Article, ApiError>,
+}
+
+type PendingImage = (String, Result<Vec<u8>, ApiError>);
+
+pub enum ImageState {
+    Loading,
+    Loaded(Arc<[u8]>),
+    Failed,
+}
+
+/// The article open in the reader pane, from the archive or fetched live.
+pub struct ArticleView {
+    pub story: StoryDisplay,
+    pub article: Option<Article>,
+    pub error: Option<ApiError>,
+    /// Whether the article came from the local archive.
+    pub archived: bool,
+    /// Images referenced by the article, keyed by URL.
+    pub images: HashMap<String, ImageState>,
+}
+
+/// State of the comment pane for the story whose discussion is open.
+pub struct CommentView {
+    pub story: StoryDisplay,
+    pub thread: Option<CommentThread>,
+    pub error: Option<ApiError>,
+    pub collapsed: HashSet<u64>,
+    /// The visit before this one, used to highlight comments added since.
+    pub previous_visit: Option<Visit>,
+}
+
+/// Results for a profile carry the `generation` of the `ProfileView` that
+/// requested them, so results for a closed or reopened profile are dropped.
+struct PendingUser {
+    generation: u64,
+    result: Result<User, ApiError>,
+}
+
+struct PendingSubmissions {
+    generation: u64,
+    items: Vec<Item>,
+    failed: Vec<(u64, ApiError)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileTab {
+    Submissions,
+    Comments,
+}
+
+/// A user's profile and the part of their submissions resolved so far.
+pub struct ProfileView {
+    generation: u64,
+    pub username: String,
+    pub user: Option<User>,
+    pub about: Vec<Block>,
+    pub error: Option<ApiError>,
+    /// Resolved items of `user.submitted`, newest first.
+    pub items: Vec<Item>,
+    /// How many ids of `user.submitted` have been requested.
+    pub requested: usize,
+    /// Requested ids that could not be fetched, until they are retried.
+    pub failed: Vec<(u64, ApiError)>,
+    pub loading_more: bool,
+    pub tab: ProfileTab,
+}
+
+impl ProfileView {
+    pub fn remaining(&self) -> usize {
+        self.user
+            .as_ref()
+            .map_or(0, |user| user.submitted.len().saturating_sub(self.requested))
+    }
+}
+
+/// The command palette's input and the highlighted entry.
+pub struct CommandPalette {
+    pub query: String,
+    pub selected: usize,
+    /// Set until the palette is first drawn, so the click that opened it
+    /// does not count as a click outside it.
+    pub opened_this_frame: bool,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewMode {
     Fetched,
     Saved,
+    Search,
+    /// Stories by watched authors or from watched domains, across all
+    /// categories.
+    Watching,
 }
 
 pub struct HackerNewsApp {
@@ -24,21 +176,131 @@ pub struct HackerNewsApp {
     pub stories: Vec<StoryDisplay>,
     pub saved_stories: Vec<StoryDisplay>,
     pub favorite_ids: HashSet<u64>,
+    pub favorite_tags: HashMap<u64, Vec<String>>,
+    pub tag_counts: Vec<(String, usize)>,
+    pub folders: Vec<Folder>,
+    pub favorite_folders: HashMap<u64, i64>,
+    /// Saved view filters: only favorites with this tag / in this folder.
+    pub tag_filter: Option<String>,
+    pub folder_filter: Option<i64>,
+    /// Text being typed into the "new folder" field and each card's tag field.
+    pub new_folder_name: String,
+    pub tag_inputs: HashMap<u64, String>,
+    /// Markdown notes of saved stories, edited in place and written back
+    /// shortly after typing pauses or when the editor loses focus.
+    pub notes: HashMap<u64, String>,
+    /// Notes edited but not yet written back, with when they last changed.
+    unsaved_notes: HashMap<u64, Instant>,
+    /// List options changed while a value was being dragged or typed, and
+    /// not yet written to config.json.
+    unsaved_list_options: bool,
+    /// When each favorite was saved, for sorting by it.
+    pub saved_at: HashMap<u64, u64>,
+    /// Saved view full-text search and its ranked matches, `None` when the
+    /// search field is empty.
+    pub saved_filter: String,
+    pub saved_matches: Option<Vec<FavoriteMatch>>,
+    /// Favorites with a stored copy of their article, those being downloaded
+    /// and the last archiving error of each.
+    pub archived_ids: HashSet<u64>,
+    pub archiving: HashSet<u64>,
+    pub archive_errors: HashMap<u64, ApiError>,
+    pub article_view: Option<ArticleView>,
+    /// The story keyboard actions apply to, and whether the list should
+    /// scroll to it on the next frame.
+    pub selected_story: Option<u64>,
+    pub scroll_to_selected: bool,
+    /// Set by the focus-search key; the search field takes focus when drawn.
+    pub focus_search: bool,
+    pub show_key_help: bool,
+    pub show_settings: bool,
+    /// Whether stories muted by the kill file are revealed in the list.
+    pub show_muted: bool,
+    /// The rule being typed into the settings window before it is added.
+    pub new_mute_rule: MuteRule,
+    /// An existing rule being edited, by index, until the edit is committed
+    /// when its field loses focus.
+    pub mute_rule_draft: Option<(usize, MuteRule)>,
+    pub command_palette: Option<CommandPalette>,
+    pub profile_view: Option<ProfileView>,
+    /// The story whose score and rank history is shown in the detail window.
+    pub history_view: Option<StoryDisplay>,
+    pub read_ids: HashSet<u64>,
+    pub visits: HashMap<u64, Visit>,
     pub is_loading: bool,
-    pub error_message: Option<String>,
+    pub is_loading_more: bool,
+    /// When the displayed stories come from the on-disk cache, the time
+    /// that snapshot was fetched.
+    pub cached_at: Option<u64>,
+    pub fetch_error: Option<ApiError>,
+    pub warning_message: Option<String>,
     pub theme: GruvboxTheme,
+    pub comment_view: Option<CommentView>,
+    pub search_query: SearchQuery,
+    pub search_results: Vec<StoryDisplay>,
+    pub is_searching: bool,
+    pub search_error: Option<ApiError>,
+    /// Watched stories collected from every category's cached snapshot,
+    /// newest first.
+    pub watched_stories: Vec<StoryDisplay>,
+    /// Categories still being fetched for the Watching view, and the last
+    /// error among them.
+    pub watch_loading: usize,
+    pub watch_error: Option<ApiError>,
+    /// First pages fetched by the last watch refresh. Their items are
+    /// cached, but the category snapshots are left alone since only their
+    /// first page is known.
+    watch_refreshed: Vec<StoryDisplay>,
+    /// Text being typed into the Watching sidebar's add fields.
+    pub new_watch_user: String,
+    pub new_watch_domain: String,
+    /// Outcome of the last export: the file written or an error message.
+    pub export_status: Option<Result<PathBuf, String>>,
+    pub import_dialog: Option<ImportDialog>,
     runtime: tokio::runtime::Runtime,
-    client: Client,
+    client: HnClient,
     db: FavoritesDB,
     config: Config,
-    pending_stories: Arc<Mutex<Option<Vec<StoryDisplay>>>>,
-    pending_error: Arc<Mutex<Option<String>>>,
+    app_config: AppConfig,
+    /// `app_config.mute_rules`, compiled.
+    kill_file: KillFile,
+    story_ids: HashMap<Category, Vec<u64>>,
+    loaded_count: usize,
+    /// Bumped whenever `stories` is replaced, so a load-more page requested
+    /// for an earlier list is not appended to the new one.
+    list_generation: u64,
+    /// Recorded history of stories shown so far, loaded on first use.
+    history: HashMap<u64, Vec<HistoryPoint>>,
+    /// First pages and load-more pages can land in the same frame, so
+    /// they queue up.
+    pending_pages: Arc<Mutex<Vec<PendingPage>>>,
+    /// Categories whose first page is being fetched. `is_loading` is set
+    /// while the current category is among them.
+    fetching: HashSet<Category>,
+    /// Warnings and errors are tagged with the category they belong to, so
+    /// a late result is not shown on another category.
+    pending_warning: Arc<Mutex<Option<(Category, String)>>>,
+    pending_errors: Arc<Mutex<Vec<(Category, ApiError)>>>,
+    /// Threads opened in quick succession can finish in the same frame.
+    pending_comments: Arc<Mutex<Vec<PendingComments>>>,
+    /// An item opened by id, whose details fill in the comment pane.
+    pending_item: Arc<Mutex<Option<Item>>>,
+    pending_user: Arc<Mutex<Option<PendingUser>>>,
+    pending_submissions: Arc<Mutex<Vec<PendingSubmissions>>>,
+    /// Incremented each time a profile is opened.
+    profile_generation: u64,
+    pending_search: Arc<Mutex<Option<SearchOutcome>>>,
+    pending_watch: Arc<Mutex<Vec<WatchOutcome>>>,
+    pending_import: Arc<Mutex<Option<ImportPreview>>>,
+    /// Archives can finish in any order, so results queue up.
+    pending_archives: Arc<Mutex<Vec<PendingArticle>>>,
+    /// Reader results queue up like archives, and only the open one is kept.
+    pending_article: Arc<Mutex<Vec<PendingArticle>>>,
+    pending_images: Arc<Mutex<Vec<PendingImage>>>,
 }
 
 impl HackerNewsApp {
     pub fn new() -> Self {
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let client = create_client();
         let db = FavoritesDB::new().unwrap_or_else(|e| {
             eprintln!("Database error: {}", e);
             panic!("Failed to open favorites database");
@@ -46,9 +308,30 @@ impl HackerNewsApp {
 
         let config = Config::new();
         let app_config = config.load();
+
+        let client = HnClient::new(&app_config).unwrap_or_else(|e| {
+            eprintln!("HTTP client error: {}", e);
+            panic!("Failed to create HTTP client");
+        });
+
+        Self::with_parts(db, config, app_config, client)
+    }
+
+    fn with_parts(db: FavoritesDB, config: Config, app_config: AppConfig, client: HnClient) -> Self {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
         let theme = app_config.theme;
 
+        if let Err(e) = db.prune_cache(CACHE_MAX_AGE_SECS) {
+            eprintln!("Failed to prune cache: {}", e);
+        }
+        if let Err(e) = db.prune_history(HISTORY_MAX_AGE_SECS) {
+            eprintln!("Failed to prune history: {}", e);
+        }
+
         let favorite_ids = Self::load_favorites(&db);
+        let read_ids = db.read_ids().unwrap_or_default();
+        let visits = db.visits().unwrap_or_default();
+        let archived_ids = db.archived_ids().unwrap_or_default();
         let saved_stories = Self::load_saved_stories(&db);
 
         let mut app = Self {
@@ -57,18 +340,85 @@ impl HackerNewsApp {
             stories: Vec::new(),
             saved_stories,
             favorite_ids,
+            favorite_tags: HashMap::new(),
+            tag_counts: Vec::new(),
+            folders: Vec::new(),
+            favorite_folders: HashMap::new(),
+            tag_filter: None,
+            folder_filter: None,
+            new_folder_name: String::new(),
+            tag_inputs: HashMap::new(),
+            notes: HashMap::new(),
+            unsaved_notes: HashMap::new(),
+            unsaved_list_options: false,
+            saved_at: HashMap::new(),
+            saved_filter: String::new(),
+            saved_matches: None,
+            archived_ids,
+            archiving: HashSet::new(),
+            archive_errors: HashMap::new(),
+            article_view: None,
+            selected_story: None,
+            scroll_to_selected: false,
+            focus_search: false,
+            show_key_help: false,
+            show_settings: false,
+            show_muted: false,
+            new_mute_rule: MuteRule::default(),
+            mute_rule_draft: None,
+            command_palette: None,
+            profile_view: None,
+            history_view: None,
+            read_ids,
+            visits,
             is_loading: false,
-            error_message: None,
+            is_loading_more: false,
+            cached_at: None,
+            fetch_error: None,
+            warning_message: None,
             theme,
+            comment_view: None,
+            search_query: SearchQuery::default(),
+            search_results: Vec::new(),
+            is_searching: false,
+            search_error: None,
+            watched_stories: Vec::new(),
+            watch_loading: 0,
+            watch_refreshed: Vec::new(),
+            watch_error: None,
+            new_watch_user: String::new(),
+            new_watch_domain: String::new(),
+            export_status: None,
+            import_dialog: None,
             runtime,
             client,
             db,
             config,
-            pending_stories: Arc::new(Mutex::new(None)),
-            pending_error: Arc::new(Mutex::new(None)),
+            kill_file: KillFile::new(&app_config.mute_rules),
+            app_config,
+            story_ids: HashMap::new(),
+            loaded_count: 0,
+            list_generation: 0,
+            history: HashMap::new(),
+            pending_pages: Arc::new(Mutex::new(Vec::new())),
+            pending_warning: Arc::new(Mutex::new(None)),
+            fetching: HashSet::new(),
+            pending_errors: Arc::new(Mutex::new(Vec::new())),
+            pending_comments: Arc::new(Mutex::new(Vec::new())),
+            pending_item: Arc::new(Mutex::new(None)),
+            pending_user: Arc::new(Mutex::new(None)),
+            pending_submissions: Arc::new(Mutex::new(Vec::new())),
+            profile_generation: 0,
+            pending_search: Arc::new(Mutex::new(None)),
+            pending_watch: Arc::new(Mutex::new(Vec::new())),
+            pending_import: Arc::new(Mutex::new(None)),
+            pending_archives: Arc::new(Mutex::new(Vec::new())),
+            pending_article: Arc::new(Mutex::new(Vec::new())),
+            pending_images: Arc::new(Mutex::new(Vec::new())),
         };
 
-        app.fetch_current_category();
+        app.reload_organisation();
+        app.select_category(Category::default());
         app
     }
 
@@ -88,53 +438,1105 @@ impl HackerNewsApp {
             .collect()
     }
 
+    /// Switches to `category`, showing its cached snapshot right away and
+    /// refetching only when the snapshot is missing or older than the
+    /// category's TTL.
+    pub fn select_category(&mut self, category: Category) {
+        self.current_category = category;
+        self.selected_story = None;
+        // A fetch still running for the previous category lands in the cache.
+        self.is_loading = self.fetching.contains(&category);
+        self.is_loading_more = false;
+        self.fetch_error = None;
+        self.warning_message = None;
+
+        if !self.show_cached_category() && !self.is_loading {
+            self.fetch_current_category();
+        }
+    }
+
+    /// Replaces the story list with the cached snapshot of the current
+    /// category. Returns whether the snapshot is still fresh.
+    fn show_cached_category(&mut self) -> bool {
+        let category = self.current_category;
+        let cached = self.db.cached_list(category).ok().flatten();
+        self.list_generation += 1;
+
+        let Some(list) = cached else {
+            self.stories.clear();
+            self.cached_at = None;
+            return false;
+        };
+
+        let page_ids = &list.ids[..list.ids.len().min(PAGE_SIZE)];
+        let mut items = self.db.cached_items(page_ids).unwrap_or_default();
+        self.stories = page_ids
+            .iter()
+            .filter_map(|id| items.remove(id))
+            .map(|item| StoryDisplay::from_story(item.into()))
+            .collect();
+        self.loaded_count = page_ids.len();
+        self.cached_at = Some(list.fetched_at);
+
+        let age = unix_now().saturating_sub(list.fetched_at);
+        self.story_ids.insert(category, list.ids);
+        age < category.cache_ttl().as_secs()
+    }
+
+    /// Caches freshly fetched items and fills in ids that failed to load
+    /// from the cache, returning the page in ranking order.
+    fn merge_with_cache(&self, page_ids: &[u64], items: Vec<Item>) -> Vec<StoryDisplay> {
+        if let Err(e) = self.db.cache_items(&items) {
+            eprintln!("Failed to cache items: {}", e);
+        }
+
+        let mut by_id: HashMap<u64, Item> = items.into_iter().map(|item| (item.id, item)).collect();
+        let missing: Vec<u64> = page_ids.iter().copied().filter(|id| !by_id.contains_key(id)).collect();
+        if !missing.is_empty() {
+            by_id.extend(self.db.cached_items(&missing).unwrap_or_default());
+        }
+
+        page_ids
+            .iter()
+            .filter_map(|id| by_id.remove(id))
+            .map(|item| StoryDisplay::from_story(item.into()))
+            .collect()
+    }
+
+    /// Fetches the current category from the network. Any cached snapshot
+    /// stays visible until the fetch completes.
     pub fn fetch_current_category(&mut self) {
+        self.fetching.insert(self.current_category);
         self.is_loading = true;
-        self.error_message = None;
+        self.is_loading_more = false;
+        self.fetch_error = None;
+        self.warning_message = None;
 
         let category = self.current_category;
         let client = self.client.clone();
-        let pending_stories = self.pending_stories.clone();
-        let pending_error = self.pending_error.clone();
+        let pending_pages = self.pending_pages.clone();
+        let pending_warning = self.pending_warning.clone();
+        let pending_errors = self.pending_errors.clone();
 
         self.runtime.spawn(async move {
             match fetch_category(&client, category).await {
-                Ok(stories) => {
-                    let displays: Vec<StoryDisplay> = stories.into_iter().map(StoryDisplay::from_story).collect();
-                    *pending_stories.lock().unwrap() = Some(displays);
+                Ok(fetched) => {
+                    if let Some(warning) = fetched.first_page.failure_summary() {
+                        *pending_warning.lock().unwrap() = Some((category, warning));
+                    }
+                    let page_ids = fetched.ids[..fetched.ids.len().min(PAGE_SIZE)].to_vec();
+                    pending_pages.lock().unwrap().push(PendingPage {
+                        category,
+                        generation: 0,
+                        ids: Some(fetched.ids),
+                        page_ids,
+                        items: fetched.first_page.items,
+                    });
                 }
                 Err(e) => {
-                    *pending_error.lock().unwrap() = Some(format!("Failed to fetch: {}", e));
+                    pending_errors.lock().unwrap().push((category, e));
                 }
             }
         });
     }
 
+    /// Fetches the next page of the current category's id list and appends it.
+    pub fn load_more(&mut self) {
+        if self.is_loading || self.is_loading_more {
+            return;
+        }
+
+        let category = self.current_category;
+        let Some(ids) = self.story_ids.get(&category) else {
+            return;
+        };
+
+        let next_ids: Vec<u64> = ids.iter().skip(self.loaded_count).take(PAGE_SIZE).copied().collect();
+        if next_ids.is_empty() {
+            return;
+        }
+        let generation = self.list_generation;
+
+        self.loaded_count += next_ids.len();
+        self.is_loading_more = true;
+        self.warning_message = None;
+
+        let client = self.client.clone();
+        let pending_pages = self.pending_pages.clone();
+        let pending_warning = self.pending_warning.clone();
+
+        self.runtime.spawn(async move {
+            let page = fetch_items(&client, &next_ids).await;
+            if let Some(warning) = page.failure_summary() {
+                *pending_warning.lock().unwrap() = Some((category, warning));
+            }
+            pending_pages.lock().unwrap().push(PendingPage {
+                category,
+                generation,
+                ids: None,
+                page_ids: next_ids,
+                items: page.items,
+            });
+        });
+    }
+
+    /// Records where each freshly fetched story of `page` ranks in its
+    /// category. Cached snapshots are never recorded.
+    fn record_history(&mut self, page: &PendingPage) {
+        let Some(ids) = page.ids.as_ref().or_else(|| self.story_ids.get(&page.category)) else {
+            return;
+        };
+
+        let stories: Vec<Story> = page.items.iter().cloned().map(Story::from).collect();
+        let ranked: Vec<(usize, &Story)> = stories
+            .iter()
+            .filter_map(|story| ids.iter().position(|&id| id == story.id).map(|i| (i + 1, story)))
+            .collect();
+        if let Err(e) = self.db.record_history(page.category, &ranked) {
+            eprintln!("Failed to record history: {}", e);
+        }
+
+        for story in &stories {
+            self.history.remove(&story.id);
+        }
+    }
+
+    /// A story's recorded score and rank history, oldest first.
+    pub fn history(&mut self, id: u64) -> &[HistoryPoint] {
+        let db = &self.db;
+        self.history
+            .entry(id)
+            .or_insert_with(|| db.story_history(id).unwrap_or_default())
+    }
+
+    pub fn open_history(&mut self, story: &StoryDisplay) {
+        self.history_view = Some(story.clone());
+    }
+
+    pub fn close_history(&mut self) {
+        self.history_view = None;
+    }
+
+    /// Number of ids in the current category that have not been fetched yet.
+    pub fn remaining_count(&self) -> usize {
+        self.story_ids
+            .get(&self.current_category)
+            .map_or(0, |ids| ids.len().saturating_sub(self.loaded_count))
+    }
+
+    /// Runs `search_query` against the search API and switches to the results.
+    pub fn run_search(&mut self) {
+        if self.search_query.text.trim().is_empty() && self.search_query.author.trim().is_empty() {
+            return;
+        }
+
+        self.view_mode = ViewMode::Search;
+        self.is_searching = true;
+        self.search_error = None;
+
+        let query = self.search_query.clone();
+        let client = self.client.clone();
+        let pending_search = self.pending_search.clone();
+
+        self.runtime.spawn(async move {
+            let result = search(&client, &query)
+                .await
+                .map(|stories| stories.into_iter().map(StoryDisplay::from_story).collect());
+            *pending_search.lock().unwrap() = Some(result);
+        });
+    }
+
+    /// Opens the comment pane for a story and fetches its discussion.
+    pub fn open_comments(&mut self, story: &StoryDisplay) {
+        self.article_view = None;
+        self.comment_view = Some(CommentView {
+            story: story.clone(),
+            thread: None,
+            error: None,
+            collapsed: HashSet::new(),
+            previous_visit: self.visits.get(&story.story.id).cloned(),
+        });
+
+        let story_id = story.story.id;
+        let client = self.client.clone();
+        let pending_comments = self.pending_comments.clone();
+
+        self.runtime.spawn(async move {
+            let result = fetch_comment_thread(&client, story_id).await;
+            pending_comments.lock().unwrap().push(PendingComments { story_id, result });
+        });
+    }
+
+    /// Opens the discussion of any item by id. Items not loaded anywhere
+    /// open right away under a placeholder title that is replaced once the
+    /// item has been fetched.
+    pub fn open_item(&mut self, id: u64) {
+        let known = self
+            .stories
+            .iter()
+            .chain(&self.saved_stories)
+            .chain(&self.search_results)
+            .find(|s| s.story.id == id)
+            .cloned()
+            .or_else(|| {
+                let item = self.db.cached_items(&[id]).ok()?.remove(&id)?;
+                Some(StoryDisplay::from_story(item.into()))
+            });
+        if let Some(story) = known {
+            self.open_comments(&story);
+            return;
+        }
+
+        self.open_comments(&StoryDisplay::from_story(Story {
+            id,
+            kind: ItemKind::Story,
+            title: format!("Item {}", id),
+            url: None,
+            by: String::new(),
+            score: 0,
+            time: 0,
+            descendants: None,
+        }));
+
+        let client = self.client.clone();
+        let pending_item = self.pending_item.clone();
+        self.runtime.spawn(async move {
+            // Failures show up through the comment thread fetch.
+            if let Ok(item) = fetch_item(&client, id).await {
+                *pending_item.lock().unwrap() = Some(item);
+            }
+        });
+    }
+
+    /// Opens the profile panel for `username` and fetches the account.
+    /// Its first page of submissions is resolved once the account arrives.
+    pub fn open_profile(&mut self, username: &str) {
+        if self.profile_view.as_ref().is_some_and(|v| v.username == username) {
+            return;
+        }
+
+        self.profile_generation += 1;
+        let generation = self.profile_generation;
+        self.profile_view = Some(ProfileView {
+            generation,
+            username: username.to_string(),
+            user: None,
+            about: Vec::new(),
+            error: None,
+            items: Vec::new(),
+            requested: 0,
+            failed: Vec::new(),
+            loading_more: false,
+            tab: ProfileTab::Submissions,
+        });
+
+        let username = username.to_string();
+        let client = self.client.clone();
+        let pending_user = self.pending_user.clone();
+
+        self.runtime.spawn(async move {
+            let result = fetch_user(&client, &username).await;
+            *pending_user.lock().unwrap() = Some(PendingUser { generation, result });
+        });
+    }
+
+    /// Resolves the next page of the open profile's submitted ids.
+    pub fn load_more_submissions(&mut self) {
+        let Some(view) = self.profile_view.as_mut() else {
+            return;
+        };
+        let Some(user) = view.user.as_ref() else {
+            return;
+        };
+        if view.loading_more {
+            return;
+        }
+
+        let ids: Vec<u64> = user.submitted.iter().skip(view.requested).take(PAGE_SIZE).copied().collect();
+        if ids.is_empty() {
+            return;
+        }
+        view.requested += ids.len();
+        self.request_submissions(ids);
+    }
+
+    /// Fetches the open profile's submissions that failed to load again.
+    pub fn retry_failed_submissions(&mut self) {
+        let Some(view) = self.profile_view.as_mut().filter(|v| !v.loading_more) else {
+            return;
+        };
+        let ids: Vec<u64> = view.failed.drain(..).map(|(id, _)| id).collect();
+        if !ids.is_empty() {
+            self.request_submissions(ids);
+        }
+    }
+
+    fn request_submissions(&mut self, ids: Vec<u64>) {
+        let Some(view) = self.profile_view.as_mut() else {
+            return;
+        };
+        view.loading_more = true;
+
+        let generation = view.generation;
+        let client = self.client.clone();
+        let pending_submissions = self.pending_submissions.clone();
+
+        self.runtime.spawn(async move {
+            let page = fetch_items(&client, &ids).await;
+            pending_submissions.lock().unwrap().push(PendingSubmissions {
+                generation,
+                items: page.items,
+                failed: page.failures,
+            });
+        });
+    }
+
+    pub fn close_profile(&mut self) {
+        self.profile_view = None;
+    }
+
+    pub fn close_comments(&mut self) {
+        self.comment_view = None;
+    }
+
     pub fn toggle_favorite(&mut self, story: &Story) {
         if self.favorite_ids.contains(&story.id) {
             let _ = self.db.remove_favorite(story.id);
             self.favorite_ids.remove(&story.id);
             self.saved_stories.retain(|s| s.story.id != story.id);
+            self.tag_inputs.remove(&story.id);
+            self.notes.remove(&story.id);
+            self.unsaved_notes.remove(&story.id);
+            self.archived_ids.remove(&story.id);
+            self.archive_errors.remove(&story.id);
+            self.reload_organisation();
+            self.search_saved();
         } else {
             let _ = self.db.add_favorite(story);
             self.favorite_ids.insert(story.id);
+            self.saved_at.insert(story.id, unix_now());
             let display = StoryDisplay::from_story(story.clone());
             self.saved_stories.push(display);
             self.saved_stories.sort_by(|a, b| b.story.time.cmp(&a.story.time));
+            self.search_saved();
+
+            if self.app_config.archive_articles {
+                self.archive_story(story);
+            }
+        }
+    }
+
+    /// Downloads the story's linked article in the background and stores its
+    /// readable content once it arrives.
+    pub fn archive_story(&mut self, story: &Story) {
+        let Some(url) = story.url.clone() else {
+            return;
+        };
+        if !self.archiving.insert(story.id) {
+            return;
+        }
+        self.archive_errors.remove(&story.id);
+
+        let story_id = story.id;
+        let client = self.client.clone();
+        let pending_archives = self.pending_archives.clone();
+
+        self.runtime.spawn(async move {
+            let result = fetch_article(&client, &url).await;
+            pending_archives.lock().unwrap().push(PendingArticle { story_id, result });
+        });
+    }
+
+    pub fn is_archived(&self, id: u64) -> bool {
+        self.archived_ids.contains(&id)
+    }
+
+    /// Opens a story's article in the reader pane, from the archive when a
+    /// copy is stored and otherwise fetched live. Text posts have no article
+    /// and open their discussion instead.
+    pub fn open_reader(&mut self, story: &StoryDisplay) {
+        let Some(url) = story.story.url.clone() else {
+            self.open_comments(story);
+            return;
+        };
+        let story_id = story.story.id;
+
+        let archived = if self.is_archived(story_id) {
+            self.db.archive(story_id).unwrap_or_else(|e| {
+                eprintln!("Failed to load archive: {}", e);
+                None
+            })
+        } else {
+            None
+        };
+
+        self.comment_view = None;
+        self.article_view = Some(ArticleView {
+            story: story.clone(),
+            archived: archived.is_some(),
+            article: archived,
+            error: None,
+            images: HashMap::new(),
+        });
+        if self.article_view.as_ref().is_some_and(|view| view.archived) {
+            return;
+        }
+
+        let client = self.client.clone();
+        let pending_article = self.pending_article.clone();
+
+        self.runtime.spawn(async move {
+            let result = fetch_article(&client, &url).await;
+            pending_article.lock().unwrap().push(PendingArticle { story_id, result });
+        });
+    }
+
+    /// Starts downloading an image shown in the reader pane.
+    pub fn load_image(&mut self, url: &str) {
+        let Some(view) = self.article_view.as_mut() else {
+            return;
+        };
+        if view.images.contains_key(url) {
+            return;
         }
+        view.images.insert(url.to_string(), ImageState::Loading);
+
+        let url = url.to_string();
+        let client = self.client.clone();
+        let pending_images = self.pending_images.clone();
+
+        self.runtime.spawn(async move {
+            let result = fetch_image(&client, &url).await;
+            pending_images.lock().unwrap().push((url, result));
+        });
+    }
+
+    pub fn open_in_reader(&self) -> bool {
+        self.app_config.open_in_reader
+    }
+
+    pub fn set_open_in_reader(&mut self, open_in_reader: bool) {
+        self.app_config.open_in_reader = open_in_reader;
+        let _ = self.config.save(&self.app_config);
+    }
+
+    pub fn close_article(&mut self) {
+        self.article_view = None;
+    }
+
+    pub fn archive_articles(&self) -> bool {
+        self.app_config.archive_articles
+    }
+
+    pub fn set_archive_articles(&mut self, archive_articles: bool) {
+        self.app_config.archive_articles = archive_articles;
+        let _ = self.config.save(&self.app_config);
     }
 
     pub fn is_favorite(&self, id: u64) -> bool {
         self.favorite_ids.contains(&id)
     }
 
+    /// Reloads tags, folders and their counts from the database. Notes still
+    /// being typed are saved first so the reload does not discard them.
+    fn reload_organisation(&mut self) {
+        self.save_pending_notes(Duration::ZERO);
+        self.favorite_tags = self.db.favorite_tags().unwrap_or_default();
+        self.tag_counts = self.db.tag_counts().unwrap_or_default();
+        self.folders = self.db.folders().unwrap_or_default();
+        self.favorite_folders = self.db.favorite_folders().unwrap_or_default();
+        self.notes = self.db.notes().unwrap_or_default();
+        self.saved_at = self.db.saved_times().unwrap_or_default();
+
+        if let Some(ref tag) = self.tag_filter {
+            if !self.tag_counts.iter().any(|(name, _)| name == tag) {
+                self.tag_filter = None;
+            }
+        }
+        if let Some(folder_id) = self.folder_filter {
+            if !self.folders.iter().any(|f| f.id == folder_id) {
+                self.folder_filter = None;
+            }
+        }
+    }
+
+    pub fn tags_of(&self, id: u64) -> &[String] {
+        self.favorite_tags.get(&id).map_or(&[], |tags| tags.as_slice())
+    }
+
+    pub fn add_tag(&mut self, id: u64, name: &str) {
+        let name = name.trim();
+        if name.is_empty() || self.tags_of(id).iter().any(|t| t.eq_ignore_ascii_case(name)) {
+            return;
+        }
+
+        if self.db.add_tag(id, name).is_ok() {
+            self.reload_organisation();
+        }
+    }
+
+    pub fn remove_tag(&mut self, id: u64, name: &str) {
+        if self.db.remove_tag(id, name).is_ok() {
+            self.reload_organisation();
+        }
+    }
+
+    pub fn create_folder(&mut self) {
+        let name = self.new_folder_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        if self.db.create_folder(&name).is_ok() {
+            self.new_folder_name.clear();
+            self.reload_organisation();
+        }
+    }
+
+    pub fn delete_folder(&mut self, folder_id: i64) {
+        if self.db.delete_folder(folder_id).is_ok() {
+            self.reload_organisation();
+        }
+    }
+
+    pub fn set_folder(&mut self, id: u64, folder_id: Option<i64>) {
+        if self.db.set_folder(id, folder_id).is_ok() {
+            self.reload_organisation();
+        }
+    }
+
+    /// Marks a favorite's notes as edited; they are saved once typing pauses.
+    pub fn notes_changed(&mut self, id: u64) {
+        self.unsaved_notes.insert(id, Instant::now());
+    }
+
+    /// Saves edited notes that have not changed for `delay`.
+    fn save_pending_notes(&mut self, delay: Duration) {
+        let due: Vec<u64> = self
+            .unsaved_notes
+            .iter()
+            .filter(|(_, edited)| edited.elapsed() >= delay)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in due {
+            self.save_notes(id);
+        }
+    }
+
+    pub fn save_notes(&mut self, id: u64) {
+        self.unsaved_notes.remove(&id);
+        let notes = self.notes.get(&id).map_or("", |n| n.as_str());
+        if let Err(e) = self.db.set_notes(id, notes) {
+            eprintln!("Failed to save notes: {}", e);
+        }
+        self.search_saved();
+    }
+
+    /// Re-runs the Saved view's full-text search for the current filter text.
+    pub fn search_saved(&mut self) {
+        if self.saved_filter.trim().is_empty() {
+            self.saved_matches = None;
+            return;
+        }
+
+        self.saved_matches = Some(self.db.search_favorites(&self.saved_filter).unwrap_or_else(|e| {
+            eprintln!("Failed to search favorites: {}", e);
+            Vec::new()
+        }));
+    }
+
+    pub fn saved_match(&self, id: u64) -> Option<&FavoriteMatch> {
+        self.saved_matches.as_ref()?.iter().find(|m| m.id == id)
+    }
+
+    /// Whether a saved story passes the Saved view's tag and folder filters.
+    pub fn matches_saved_filters(&self, id: u64) -> bool {
+        let tag_ok = self
+            .tag_filter
+            .as_ref()
+            .is_none_or(|tag| self.tags_of(id).iter().any(|t| t == tag));
+        let folder_ok = self
+            .folder_filter
+            .is_none_or(|folder_id| self.favorite_folders.get(&id) == Some(&folder_id));
+        tag_ok && folder_ok
+    }
+
+    /// Writes all favorites to a new file in the exports directory.
+    pub fn export_favorites(&mut self, format: ExportFormat) {
+        let path = export::default_path(format);
+        let result = self
+            .db
+            .saved_favorites()
+            .map_err(|e| e.to_string())
+            .and_then(|favorites| export::write(&path, &favorites, format).map_err(|e| e.to_string()));
+
+        self.export_status = Some(result.map(|_| path));
+    }
+
+    pub fn open_import_dialog(&mut self) {
+        self.import_dialog = Some(ImportDialog {
+            path: String::new(),
+            stage: ImportStage::Choosing,
+        });
+    }
+
+    pub fn close_import_dialog(&mut self) {
+        self.import_dialog = None;
+    }
+
+    /// Parses the chosen file and resolves its ids in the background; the
+    /// preview shows up once `check_pending_updates` picks it up.
+    pub fn load_import(&mut self) {
+        let Some(dialog) = self.import_dialog.as_mut() else {
+            return;
+        };
+
+        let path = PathBuf::from(dialog.path.trim());
+        let (source, records) = match import::parse_file(&path) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                dialog.stage = ImportStage::Failed(e.to_string());
+                return;
+            }
+        };
+        dialog.stage = ImportStage::Resolving;
+
+        let client = self.client.clone();
+        let existing = self.favorite_ids.clone();
+        let pending_import = self.pending_import.clone();
+
+        self.runtime.spawn(async move {
+            let preview = import::resolve(&client, source, records, &existing).await;
+            *pending_import.lock().unwrap() = Some(preview);
+        });
+    }
+
+    /// Saves the entries selected in the preview.
+    pub fn commit_import(&mut self) {
+        let Some(dialog) = self.import_dialog.as_mut() else {
+            return;
+        };
+        let ImportStage::Preview(ref preview) = dialog.stage else {
+            return;
+        };
+
+        dialog.stage = match self.db.import_favorites(&preview.selected()) {
+            Ok(added) => ImportStage::Done(added),
+            Err(e) => ImportStage::Failed(e.to_string()),
+        };
+
+        self.favorite_ids = Self::load_favorites(&self.db);
+        self.saved_stories = Self::load_saved_stories(&self.db);
+        self.reload_organisation();
+        self.search_saved();
+    }
+
+    /// Comments added to a story since its comments were last opened, if
+    /// they have been opened before.
+    pub fn new_comment_count(&self, story: &Story) -> Option<u64> {
+        let visit = self.visits.get(&story.id)?;
+        Some(visit.new_comments(story.descendants.unwrap_or(0)))
+    }
+
+    pub fn is_read(&self, id: u64) -> bool {
+        self.read_ids.contains(&id)
+    }
+
+    pub fn mark_read(&mut self, id: u64) {
+        if self.read_ids.insert(id) {
+            let _ = self.db.mark_read(&[id]);
+        }
+    }
+
+    /// Marks the stories listed in the active view as read.
+    pub fn mark_all_read(&mut self) {
+        let ids: Vec<u64> = self
+            .visible_stories()
+            .into_iter()
+            .map(|(story, _)| story.story.id)
+            .filter(|id| !self.read_ids.contains(id))
+            .collect();
+
+        if self.db.mark_read(&ids).is_ok() {
+            self.read_ids.extend(ids);
+        }
+    }
+
+    pub fn hide_read(&self) -> bool {
+        self.app_config.hide_read
+    }
+
+    pub fn set_hide_read(&mut self, hide_read: bool) {
+        self.app_config.hide_read = hide_read;
+        let _ = self.config.save(&self.app_config);
+    }
+
+    pub fn list_options(&self) -> &ListOptions {
+        &self.app_config.list_options
+    }
+
+    /// Applies list options straight away but only writes them out when
+    /// `save_list_options` is called, so dragging a value doesn't rewrite
+    /// config.json every frame.
+    pub fn set_list_options(&mut self, options: ListOptions) {
+        self.app_config.list_options = options;
+        self.unsaved_list_options = true;
+    }
+
+    pub fn save_list_options(&mut self) {
+        if std::mem::take(&mut self.unsaved_list_options) {
+            let _ = self.config.save(&self.app_config);
+        }
+    }
+
+    pub fn mute_rules(&self) -> &[MuteRule] {
+        &self.app_config.mute_rules
+    }
+
+    pub fn set_mute_rules(&mut self, rules: Vec<MuteRule>) {
+        self.kill_file = KillFile::new(&rules);
+        self.app_config.mute_rules = rules;
+        let _ = self.config.save(&self.app_config);
+    }
+
+    /// Adds `new_mute_rule` unless its pattern is blank.
+    pub fn add_mute_rule(&mut self) {
+        if self.new_mute_rule.pattern.trim().is_empty() {
+            return;
+        }
+        let rule = std::mem::take(&mut self.new_mute_rule);
+        let mut rules = self.app_config.mute_rules.clone();
+        rules.push(MuteRule {
+            kind: rule.kind,
+            pattern: rule.pattern.trim().to_string(),
+        });
+        self.set_mute_rules(rules);
+        self.new_mute_rule.kind = rule.kind;
+    }
+
+    /// The kill-file rule hiding `story`, if any.
+    pub fn muted_by(&self, story: &StoryDisplay) -> Option<&MuteRule> {
+        self.kill_file.matching_rule(story)
+    }
+
+    /// The stories the current view shows, filtered and sorted, each with
+    /// whether it is a favorite.
+    pub fn visible_stories(&self) -> Vec<(StoryDisplay, bool)> {
+        self.list_stories().0
+    }
+
+    /// Like `visible_stories`, also counting the stories the kill file
+    /// mutes. Muted stories stay in the list while `show_muted` is set.
+    /// Favorites were saved on purpose and are never muted.
+    pub fn list_stories(&self) -> (Vec<(StoryDisplay, bool)>, usize) {
+        let hide_read = self.hide_read();
+        let mut stories: Vec<(StoryDisplay, bool)> = match self.view_mode {
+            ViewMode::Fetched => self
+                .stories
+                .iter()
+                .filter(|s| !(hide_read && self.is_read(s.story.id)))
+                .map(|s| (s.clone(), self.is_favorite(s.story.id)))
+                .collect(),
+            ViewMode::Saved => match &self.saved_matches {
+                // Full-text matches come back in rank order.
+                Some(matches) => matches
+                    .iter()
+                    .filter(|m| self.matches_saved_filters(m.id))
+                    .filter_map(|m| self.saved_stories.iter().find(|s| s.story.id == m.id))
+                    .map(|s| (s.clone(), true))
+                    .collect(),
+                None => self
+                    .saved_stories
+                    .iter()
+                    .filter(|s| self.matches_saved_filters(s.story.id))
+                    .map(|s| (s.clone(), true))
+                    .collect(),
+            },
+            ViewMode::Search => self
+                .search_results
+                .iter()
+                .filter(|s| !(hide_read && self.is_read(s.story.id)))
+                .map(|s| (s.clone(), self.is_favorite(s.story.id)))
+                .collect(),
+            ViewMode::Watching => self
+                .watched_stories
+                .iter()
+                .filter(|s| !(hide_read && self.is_read(s.story.id)))
+                .map(|s| (s.clone(), self.is_favorite(s.story.id)))
+                .collect(),
+        };
+
+        let options = self.list_options();
+        let now = unix_now();
+        stories.retain(|(story, _)| options.matches(story, now));
+
+        let mut muted = 0;
+        stories.retain(|(story, is_favorite)| {
+            let is_muted = !is_favorite && self.kill_file.is_muted(story);
+            muted += usize::from(is_muted);
+            !is_muted || self.show_muted
+        });
+
+        options.sort(&mut stories, |(story, _)| story, &self.saved_at);
+        (stories, muted)
+    }
+
     pub fn set_view_mode(&mut self, mode: ViewMode) {
         self.view_mode = mode;
-        self.error_message = None;
+        self.selected_story = None;
+        self.fetch_error = None;
 
         if mode == ViewMode::Fetched && self.stories.is_empty() && !self.is_loading {
             self.fetch_current_category();
         }
+        if mode == ViewMode::Watching {
+            self.collect_watched();
+        }
+    }
+
+    pub fn watch_list(&self) -> &WatchList {
+        &self.app_config.watch_list
+    }
+
+    pub fn watch(&mut self, kind: WatchKind, name: &str) {
+        if self.app_config.watch_list.add(kind, name) {
+            self.save_watch_list();
+        }
+    }
+
+    pub fn unwatch(&mut self, kind: WatchKind, name: &str) {
+        self.app_config.watch_list.remove(kind, name);
+        self.save_watch_list();
+    }
+
+    pub fn cycle_watch_color(&mut self, kind: WatchKind, index: usize) {
+        self.app_config.watch_list.cycle_color(kind, index);
+        let _ = self.config.save(&self.app_config);
+    }
+
+    fn save_watch_list(&mut self) {
+        let _ = self.config.save(&self.app_config);
+        if self.view_mode == ViewMode::Watching {
+            self.collect_watched();
+        }
+    }
+
+    /// Gathers the stories matching the watch list from every category's
+    /// cached snapshot, the last watch refresh, the current list, search
+    /// results and favorites.
+    fn collect_watched(&mut self) {
+        let watch_list = &self.app_config.watch_list;
+        let mut seen = HashSet::new();
+        let mut watched: Vec<StoryDisplay> = Vec::new();
+        let mut add = |story: StoryDisplay| {
+            if watch_list.is_match(&story) && seen.insert(story.story.id) {
+                watched.push(story);
+            }
+        };
+
+        if !watch_list.is_empty() {
+            for category in Category::ALL {
+                let Some(list) = self.db.cached_list(category).ok().flatten() else {
+                    continue;
+                };
+                let items = self.db.cached_items(&list.ids).unwrap_or_default();
+                for item in items.into_values() {
+                    add(StoryDisplay::from_story(item.into()));
+                }
+            }
+            let lists = [&self.watch_refreshed, &self.stories, &self.search_results, &self.saved_stories];
+            for story in lists.into_iter().flatten() {
+                add(story.clone());
+            }
+        }
+
+        watched.sort_by_key(|s| std::cmp::Reverse(s.story.time));
+        self.watched_stories = watched;
+    }
+
+    /// Fetches the first page of every category in the background; the
+    /// Watching view refills as each arrives.
+    pub fn refresh_watching(&mut self) {
+        if self.watch_loading > 0 {
+            return;
+        }
+        self.watch_loading = Category::ALL.len();
+        self.watch_error = None;
+        self.watch_refreshed.clear();
+
+        for category in Category::ALL {
+            let client = self.client.clone();
+            let pending_watch = self.pending_watch.clone();
+
+            self.runtime.spawn(async move {
+                let outcome = fetch_category(&client, category).await.map(|fetched| {
+                    let page_ids = fetched.ids[..fetched.ids.len().min(PAGE_SIZE)].to_vec();
+                    PendingPage {
+                        category,
+                        generation: 0,
+                        ids: Some(fetched.ids),
+                        page_ids,
+                        items: fetched.first_page.items,
+                    }
+                });
+                pending_watch.lock().unwrap().push(outcome);
+            });
+        }
+    }
+
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.app_config.key_bindings
+    }
+
+    /// Runs the actions whose keys were pressed this frame. Keys typed into
+    /// a text field are left to it.
+    fn handle_keys(&mut self, ctx: &egui::Context) {
+        // While typing or in the palette, only shortcuts with Ctrl or Cmd
+        // are taken.
+        let typing = ctx.wants_keyboard_input() || self.command_palette.is_some();
+        if self.show_key_help && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.show_key_help = false;
+        }
+
+        let bindings = &self.app_config.key_bindings;
+        let pressed: Vec<Action> = ctx.input_mut(|input| {
+            Action::ALL
+                .iter()
+                .copied()
+                .filter(|&action| {
+                    bindings
+                        .shortcuts(action)
+                        .iter()
+                        .filter(|s| !typing || s.modifiers.command || s.modifiers.ctrl)
+                        .any(|s| input.consume_shortcut(s))
+                })
+                .collect()
+        });
+        for action in pressed {
+            self.perform(action);
+        }
+    }
+
+    pub fn perform(&mut self, action: Action) {
+        match action {
+            Action::SelectNext => self.move_selection(1),
+            Action::SelectPrevious => self.move_selection(-1),
+            Action::OpenSelected => {
+                if let Some(story) = self.selected() {
+                    self.mark_read(story.story.id);
+                    if self.open_in_reader() {
+                        self.open_reader(&story);
+                    } else {
+                        let url = story.story.url.clone().unwrap_or_else(|| story.hn_url());
+                        opener::open(url.as_str()).ok();
+                    }
+                }
+            }
+            Action::OpenComments => {
+                if let Some(story) = self.selected() {
+                    self.mark_read(story.story.id);
+                    self.open_comments(&story);
+                }
+            }
+            Action::ToggleFavorite => {
+                if let Some(story) = self.selected() {
+                    self.toggle_favorite(&story.story);
+                }
+            }
+            Action::Refresh => match self.view_mode {
+                ViewMode::Fetched => self.fetch_current_category(),
+                ViewMode::Search => self.run_search(),
+                ViewMode::Watching => self.refresh_watching(),
+                ViewMode::Saved => {}
+            },
+            Action::ShowCategory(category) => {
+                self.select_category(category);
+                self.set_view_mode(ViewMode::Fetched);
+            }
+            Action::ShowView(mode) => self.set_view_mode(mode),
+            Action::ToggleTheme => self.toggle_theme(),
+            Action::ToggleHideRead => self.set_hide_read(!self.hide_read()),
+            Action::MarkAllRead => self.mark_all_read(),
+            Action::ToggleMuted => self.show_muted = !self.show_muted,
+            Action::FocusSearch => self.focus_search = true,
+            Action::Export(format) => self.export_favorites(format),
+            Action::Import => self.open_import_dialog(),
+            Action::OpenItem(id) => self.open_item(id),
+            Action::CommandPalette => {
+                self.command_palette = match self.command_palette {
+                    Some(_) => None,
+                    None => Some(CommandPalette {
+                        query: String::new(),
+                        selected: 0,
+                        opened_this_frame: true,
+                    }),
+                };
+            }
+            Action::ToggleSettings => self.show_settings = !self.show_settings,
+            Action::ToggleHelp => self.show_key_help = !self.show_key_help,
+        }
+    }
+
+    /// The palette's entries for what has been typed into it.
+    pub fn palette_commands(&self) -> Vec<Command> {
+        let query = self.command_palette.as_ref().map_or("", |p| p.query.as_str());
+        palette::commands(query, &self.app_config.recent_commands)
+    }
+
+    /// Closes the palette and runs `command`, remembering it for ranking.
+    pub fn run_command(&mut self, command: Command) {
+        self.command_palette = None;
+
+        match command {
+            Command::Action(action) => {
+                if !matches!(action, Action::OpenItem(_)) {
+                    let name = action.name();
+                    let recent = &mut self.app_config.recent_commands;
+                    recent.retain(|r| *r != name);
+                    recent.insert(0, name);
+                    recent.truncate(palette::MAX_RECENT);
+                    let _ = self.config.save(&self.app_config);
+                }
+                self.perform(action);
+            }
+            Command::Search(text) => {
+                self.search_query.text = text;
+                self.set_view_mode(ViewMode::Search);
+                self.run_search();
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<StoryDisplay> {
+        let id = self.selected_story?;
+        self.visible_stories()
+            .into_iter()
+            .map(|(story, _)| story)
+            .find(|story| story.story.id == id)
+    }
+
+    /// Moves the selection `step` stories down the visible list, starting
+    /// from the top when nothing is selected. Moving past the last fetched
+    /// story loads the next page.
+    fn move_selection(&mut self, step: isize) {
+        let stories = self.visible_stories();
+        if stories.is_empty() {
+            return;
+        }
+
+        let current = self
+            .selected_story
+            .and_then(|id| stories.iter().position(|(s, _)| s.story.id == id));
+        let index = match current {
+            Some(i) => (i as isize + step).clamp(0, stories.len() as isize - 1) as usize,
+            None => 0,
+        };
+        if current == Some(stories.len() - 1) && step > 0 && self.view_mode == ViewMode::Fetched {
+            self.load_more();
+        }
+
+        self.selected_story = Some(stories[index].0.story.id);
+        self.scroll_to_selected = true;
     }
 
     pub fn toggle_theme(&mut self) {
@@ -143,24 +1545,251 @@ impl HackerNewsApp {
             GruvboxTheme::Light => GruvboxTheme::Dark,
         };
 
-        let updated_config = crate::config::AppConfig {
-            theme: self.theme,
-        };
-        let _ = self.config.save(&updated_config);
+        self.app_config.theme = self.theme;
+        let _ = self.config.save(&self.app_config);
     }
 
     fn check_pending_updates(&mut self) {
-        if let Ok(mut guard) = self.pending_stories.try_lock() {
-            if let Some(stories) = guard.take() {
-                self.stories = stories;
-                self.is_loading = false;
-                self.error_message = None;
+        let pages: Vec<PendingPage> = match self.pending_pages.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        // New stories may match the watch list.
+        let recollect_watched = !pages.is_empty() && self.view_mode == ViewMode::Watching;
+        for page in pages {
+            self.record_history(&page);
+            if page.ids.is_some() {
+                self.fetching.remove(&page.category);
+            }
+
+            if page.category != self.current_category {
+                // Keep what was fetched for the next visit to that category.
+                if let Some(ids) = &page.ids {
+                    if let Err(e) = self.db.cache_list(page.category, ids) {
+                        eprintln!("Failed to cache story list: {}", e);
+                    }
+                }
+                if let Err(e) = self.db.cache_items(&page.items) {
+                    eprintln!("Failed to cache items: {}", e);
+                }
+                self.is_loading = self.fetching.contains(&self.current_category);
+                continue;
+            }
+
+            if page.ids.is_none() && page.generation != self.list_generation {
+                // Requested before the list was refreshed or reloaded from
+                // the cache; its stories may already be shown.
+                if let Err(e) = self.db.cache_items(&page.items) {
+                    eprintln!("Failed to cache items: {}", e);
+                }
+                continue;
+            }
+
+            let stories = self.merge_with_cache(&page.page_ids, page.items);
+            match page.ids {
+                Some(ids) => {
+                    if let Err(e) = self.db.cache_list(page.category, &ids) {
+                        eprintln!("Failed to cache story list: {}", e);
+                    }
+                    self.loaded_count = page.page_ids.len();
+                    self.list_generation += 1;
+                    self.story_ids.insert(page.category, ids);
+                    self.stories = stories;
+                    self.cached_at = None;
+                    self.is_loading = false;
+                    self.fetch_error = None;
+                }
+                None => {
+                    self.stories.extend(stories);
+                    self.is_loading_more = false;
+                }
+            }
+        }
+
+        if recollect_watched {
+            self.collect_watched();
+        }
+
+        let watched: Vec<WatchOutcome> = match self.pending_watch.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        if !watched.is_empty() {
+            for outcome in watched {
+                self.watch_loading = self.watch_loading.saturating_sub(1);
+                match outcome {
+                    Ok(page) => {
+                        self.record_history(&page);
+                        if let Err(e) = self.db.cache_items(&page.items) {
+                            eprintln!("Failed to cache items: {}", e);
+                        }
+                        self.watch_refreshed
+                            .extend(page.items.into_iter().map(|item| StoryDisplay::from_story(item.into())));
+                    }
+                    Err(e) => self.watch_error = Some(e),
+                }
+            }
+            self.collect_watched();
+        }
+
+        if let Ok(mut guard) = self.pending_warning.try_lock() {
+            if let Some((category, warning)) = guard.take() {
+                if category == self.current_category {
+                    self.warning_message = Some(warning);
+                }
+            }
+        }
+
+        let finished: Vec<PendingArticle> = match self.pending_archives.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for pending in finished {
+            self.archiving.remove(&pending.story_id);
+            // The story may have been unstarred while it was downloading.
+            if !self.favorite_ids.contains(&pending.story_id) {
+                continue;
+            }
+
+            match pending.result {
+                Ok(article) => match self.db.save_archive(pending.story_id, &article) {
+                    Ok(()) => {
+                        self.archived_ids.insert(pending.story_id);
+                        self.search_saved();
+                    }
+                    Err(e) => eprintln!("Failed to save archive: {}", e),
+                },
+                Err(e) => {
+                    self.archive_errors.insert(pending.story_id, e);
+                }
+            }
+        }
+
+        let articles: Vec<PendingArticle> = match self.pending_article.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for pending in articles {
+            if let Some(view) = self
+                .article_view
+                .as_mut()
+                .filter(|v| v.story.story.id == pending.story_id)
+            {
+                match pending.result {
+                    Ok(article) => view.article = Some(article),
+                    Err(e) => view.error = Some(e),
+                }
+            }
+        }
+
+        let images: Vec<PendingImage> = match self.pending_images.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        if let Some(view) = self.article_view.as_mut() {
+            for (url, result) in images {
+                // Only images of the article still open are kept.
+                if let Some(state) = view.images.get_mut(&url) {
+                    *state = match result {
+                        Ok(bytes) => ImageState::Loaded(bytes.into()),
+                        Err(_) => ImageState::Failed,
+                    };
+                }
+            }
+        }
+
+        if let Ok(mut guard) = self.pending_import.try_lock() {
+            if let Some(preview) = guard.take() {
+                if let Some(dialog) = self.import_dialog.as_mut() {
+                    dialog.stage = ImportStage::Preview(preview);
+                }
+            }
+        }
+
+        let comments: Vec<PendingComments> = match self.pending_comments.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for pending in comments {
+            // Threads of views closed since are dropped.
+            let Some(view) = self.comment_view.as_mut().filter(|v| v.story.story.id == pending.story_id) else {
+                continue;
+            };
+            match pending.result {
+                Ok(thread) => {
+                    let descendants = thread.descendants.or(view.story.story.descendants).unwrap_or(0);
+                    if let Ok(visit) = self.db.record_visit(pending.story_id, descendants, &thread.root_kids) {
+                        self.visits.insert(pending.story_id, visit);
+                    }
+                    view.thread = Some(thread);
+                }
+                Err(error) => view.error = Some(error),
+            }
+        }
+
+        if let Ok(mut guard) = self.pending_item.try_lock() {
+            if let Some(item) = guard.take() {
+                if let Some(view) = self.comment_view.as_mut().filter(|v| v.story.story.id == item.id) {
+                    view.story = StoryDisplay::from_story(item.into());
+                }
             }
         }
 
-        if let Ok(mut guard) = self.pending_error.try_lock() {
-            if let Some(error) = guard.take() {
-                self.error_message = Some(error);
+        let user = match self.pending_user.try_lock() {
+            Ok(mut guard) => guard.take(),
+            Err(_) => None,
+        };
+        if let Some(pending) = user {
+            if let Some(view) = self.profile_view.as_mut().filter(|v| v.generation == pending.generation) {
+                match pending.result {
+                    Ok(user) => {
+                        view.about = user.about.as_deref().map(html::parse).unwrap_or_default();
+                        view.user = Some(user);
+                        self.load_more_submissions();
+                    }
+                    Err(e) => view.error = Some(e),
+                }
+            }
+        }
+
+        let submissions: Vec<PendingSubmissions> = match self.pending_submissions.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for pending in submissions {
+            let Some(view) = self.profile_view.as_mut().filter(|v| v.generation == pending.generation) else {
+                continue;
+            };
+            view.items.extend(pending.items);
+            view.failed.extend(pending.failed);
+            view.loading_more = false;
+
+            // Retried items land late; keep the newest-first order of `submitted`.
+            if let Some(user) = view.user.as_ref() {
+                let position: HashMap<u64, usize> =
+                    user.submitted.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+                view.items.sort_by_key(|item| position.get(&item.id).copied().unwrap_or(usize::MAX));
+            }
+        }
+
+        if let Ok(mut guard) = self.pending_search.try_lock() {
+            if let Some(result) = guard.take() {
+                match result {
+                    Ok(results) => self.search_results = results,
+                    Err(error) => self.search_error = Some(error),
+                }
+                self.is_searching = false;
+            }
+        }
+
+        let errors: Vec<(Category, ApiError)> = match self.pending_errors.try_lock() {
+            Ok(mut guard) => guard.drain(..).collect(),
+            Err(_) => Vec::new(),
+        };
+        for (category, error) in errors {
+            self.fetching.remove(&category);
+            if category == self.current_category {
+                self.fetch_error = Some(error);
                 self.is_loading = false;
             }
         }
@@ -170,12 +1799,127 @@ impl HackerNewsApp {
 impl eframe::App for HackerNewsApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.check_pending_updates();
+        self.save_pending_notes(NOTES_SAVE_DELAY);
+        self.handle_keys(ctx);
 
         apply_theme(ctx, &self.theme);
 
+        ui::render_menu_bar(ctx, self);
         ui::render_sidebar(ctx, self);
+        ui::render_profile(ctx, self);
+        ui::render_comments(ctx, self);
+        ui::render_article(ctx, self);
+        ui::render_import_dialog(ctx, self);
+        ui::render_history(ctx, self);
+        ui::render_key_help(ctx, self);
+        ui::render_settings(ctx, self);
+        ui::render_command_palette(ctx, self);
         ui::render_story_list(ctx, self);
 
         ctx.request_repaint();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{FixtureTransport, Transport, TransportFuture};
+
+    const API: &str = "https://hacker-news.firebaseio.com/v0";
+
+    /// Serves fixtures, answering items past the first page slowly.
+    struct SlowSecondPage {
+        fixtures: FixtureTransport,
+    }
+
+    impl Transport for SlowSecondPage {
+        fn get<'a>(&'a self, url: &'a str, timeout: Duration) -> TransportFuture<'a> {
+            Box::pin(async move {
+                let id: usize = url
+                    .trim_end_matches(".json")
+                    .rsplit('/')
+                    .next()
+                    .and_then(|id| id.parse().ok())
+                    .unwrap_or(0);
+                if id > PAGE_SIZE {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                self.fixtures.get(url, timeout).await
+            })
+        }
+
+        fn get_bytes<'a>(
+            &'a self,
+            url: &'a str,
+            timeout: Duration,
+            max_bytes: usize,
+        ) -> TransportFuture<'a, Vec<u8>> {
+            self.fixtures.get_bytes(url, timeout, max_bytes)
+        }
+    }
+
+    /// An app with its database and config in a fresh temporary directory.
+    fn test_app(name: &str, transport: Arc<dyn Transport>) -> HackerNewsApp {
+        let dir = std::env::temp_dir().join(format!("my_egui_pro-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let db = FavoritesDB::open(&dir.join("favorites.db")).unwrap();
+        let config = Config::at(dir.join("config.json"));
+        let app_config = AppConfig::default();
+        let client = HnClient::with_transport(&app_config, transport);
+        HackerNewsApp::with_parts(db, config, app_config, client)
+    }
+
+    fn wait_until(app: &mut HackerNewsApp, done: impl Fn(&HackerNewsApp) -> bool) {
+        for _ in 0..300 {
+            app.check_pending_updates();
+            if done(app) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out waiting for background results");
+    }
+
+    fn shown_ids(app: &HackerNewsApp) -> Vec<u64> {
+        app.stories.iter().map(|s| s.story.id).collect()
+    }
+
+    #[test]
+    fn refresh_drops_a_load_more_page_still_in_flight() {
+        let ids: Vec<u64> = (1..=2 * PAGE_SIZE as u64).collect();
+        let mut fixtures = FixtureTransport::default();
+        fixtures.insert(format!("{}/topstories.json", API), serde_json::to_string(&ids).unwrap());
+        for &id in &ids {
+            fixtures.insert(
+                format!("{}/item/{}.json", API, id),
+                format!(r#"{{"id": {}, "type": "story", "title": "Story {}", "by": "pg", "time": 1}}"#, id, id),
+            );
+        }
+        let mut app = test_app("refresh-during-load-more", Arc::new(SlowSecondPage { fixtures }));
+        let first_page: Vec<u64> = ids[..PAGE_SIZE].to_vec();
+
+        wait_until(&mut app, |app| !app.is_loading);
+        assert_eq!(shown_ids(&app), first_page);
+
+        app.load_more();
+        assert!(app.is_loading_more);
+        app.fetch_current_category();
+        wait_until(&mut app, |app| !app.is_loading);
+
+        // Let the slow page from before the refresh land.
+        for _ in 0..300 {
+            if !app.pending_pages.lock().unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        app.check_pending_updates();
+        assert_eq!(shown_ids(&app), first_page);
+        assert_eq!(app.remaining_count(), PAGE_SIZE);
+
+        app.load_more();
+        wait_until(&mut app, |app| !app.is_loading_more);
+        assert_eq!(shown_ids(&app), ids);
+    }
+}
@@ -0,0 +1,624 @@
+//! Fetches linked pages and extracts their readable main content, in the
+//! spirit of Readability: paragraphs are scored, the best-scoring container
+//! wins, and its headings, paragraphs, lists, code, quotes and images are kept.
+
+use crate::hn_api::{ApiError, HnClient};
+use regex::Regex;
+use reqwest::Url;
+use scraper::{node::Node, ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+pub const DEFAULT_ARTICLE_TIMEOUT_SECS: u64 = 15;
+/// Larger pages and images are refused rather than read into memory.
+pub const MAX_ARTICLE_BYTES: usize = 5 * 1024 * 1024;
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Class and id fragments that mark likely content and likely boilerplate.
+static POSITIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|main|page|post|text|blog|story").unwrap()
+});
+static NEGATIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)comment|meta|footer|footnote|sidebar|nav|menu|share|social|promo|related|advert|banner|sponsor|popup|cookie|subscribe|newsletter",
+    )
+    .unwrap()
+});
+
+/// Elements that never hold article content.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "nav", "footer", "aside", "form", "iframe", "svg", "button",
+    "input", "select", "template", "head",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ArticleBlock {
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Paragraph(String),
+    /// List items in order. A list nested inside an item follows that item
+    /// as its own block, one `depth` deeper.
+    List {
+        ordered: bool,
+        items: Vec<String>,
+        #[serde(default)]
+        depth: u8,
+    },
+    Code(String),
+    Quote(String),
+    Image {
+        src: String,
+        alt: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Article {
+    pub url: String,
+    pub title: Option<String>,
+    pub blocks: Vec<ArticleBlock>,
+}
+
+impl Article {
+    /// The article's text without markup, one block per line.
+    pub fn plain_text(&self) -> String {
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                ArticleBlock::Heading { text, .. }
+                | ArticleBlock::Paragraph(text)
+                | ArticleBlock::Code(text)
+                | ArticleBlock::Quote(text) => Some(text.clone()),
+                ArticleBlock::List { items, .. } => Some(items.join("\n")),
+                ArticleBlock::Image { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.plain_text().split_whitespace().count()
+    }
+}
+
+/// Downloads `url` and extracts its main content.
+pub async fn fetch_article(client: &HnClient, url: &str) -> Result<Article, ApiError> {
+    let body = client
+        .get_bytes(url, client.article_timeout(), MAX_ARTICLE_BYTES)
+        .await?;
+    let article = extract(&String::from_utf8_lossy(&body), url);
+
+    if article.blocks.is_empty() {
+        return Err(ApiError::Decode {
+            item_id: None,
+            message: "no readable content found on the page".to_string(),
+        });
+    }
+    Ok(article)
+}
+
+/// Downloads an image referenced by an article.
+pub async fn fetch_image(client: &HnClient, url: &str) -> Result<Vec<u8>, ApiError> {
+    client
+        .get_bytes(url, client.article_timeout(), MAX_IMAGE_BYTES)
+        .await
+}
+
+/// Extracts the main content of an HTML page. Relative image links are
+/// resolved against `url`.
+pub fn extract(html: &str, url: &str) -> Article {
+    let document = Html::parse_document(html);
+    let base = Url::parse(url).ok();
+
+    let mut builder = Builder {
+        base,
+        blocks: Vec::new(),
+        text: String::new(),
+    };
+    if let Some(root) = content_root(&document) {
+        builder.walk(root);
+        builder.flush();
+    }
+
+    Article {
+        url: url.to_string(),
+        title: title(&document),
+        blocks: builder.blocks,
+    }
+}
+
+fn title(document: &Html) -> Option<String> {
+    let og_title = Selector::parse(r#"meta[property="og:title"]"#).unwrap();
+    let title = Selector::parse("title").unwrap();
+
+    document
+        .select(&og_title)
+        .find_map(|meta| meta.attr("content"))
+        .map(str::to_string)
+        .or_else(|| document.select(&title).next().map(|t| t.text().collect()))
+        .map(|t| collapse_whitespace(&t))
+        .filter(|t| !t.is_empty())
+}
+
+fn is_skipped(element: &ElementRef) -> bool {
+    let name = element.value().name();
+    SKIPPED.contains(&name) || (name != "body" && class_weight(element) < 0.0)
+}
+
+fn class_weight(element: &ElementRef) -> f64 {
+    let value = element.value();
+    let names = format!(
+        "{} {}",
+        value.attr("class").unwrap_or(""),
+        value.id().unwrap_or("")
+    );
+    if names.trim().is_empty() {
+        return 0.0;
+    }
+
+    let mut weight = 0.0;
+    if NEGATIVE.is_match(&names) {
+        weight -= 25.0;
+    }
+    if POSITIVE.is_match(&names) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn in_skipped(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| is_skipped(&ancestor))
+}
+
+/// Scores every paragraph, credits its parent fully and its grandparent by
+/// half, and returns the best-scoring container.
+fn content_root(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraphs = Selector::parse("p, pre, td").unwrap();
+    let mut scores: HashMap<_, f64> = HashMap::new();
+
+    for paragraph in document.select(&paragraphs) {
+        if is_skipped(&paragraph) || in_skipped(&paragraph) {
+            continue;
+        }
+
+        let text = collapse_whitespace(&paragraph.text().collect::<String>());
+        if text.chars().count() < 25 {
+            continue;
+        }
+        let score =
+            1.0 + text.matches(',').count() as f64 + (text.chars().count() as f64 / 100.0).min(3.0);
+
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        for share in [1.0, 0.5] {
+            let Some(ancestor) = ancestors.next() else {
+                break;
+            };
+            let entry = scores
+                .entry(ancestor.id())
+                .or_insert_with(|| class_weight(&ancestor) + tag_weight(ancestor.value().name()));
+            *entry += score * share;
+        }
+    }
+
+    let best = scores
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .and_then(|(id, _)| document.tree.get(id))
+        .and_then(ElementRef::wrap);
+
+    best.or_else(|| {
+        let fallback = Selector::parse("article, main, body").unwrap();
+        document.select(&fallback).next()
+    })
+}
+
+fn tag_weight(name: &str) -> f64 {
+    match name {
+        "article" => 30.0,
+        "main" => 20.0,
+        "div" | "section" => 5.0,
+        "td" | "blockquote" => 3.0,
+        "body" => -10.0,
+        _ => 0.0,
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Walks the content root, collecting inline text into paragraphs and
+/// emitting block elements as they are met.
+struct Builder {
+    base: Option<Url>,
+    blocks: Vec<ArticleBlock>,
+    text: String,
+}
+
+impl Builder {
+    fn flush(&mut self) {
+        let text = collapse_whitespace(&std::mem::take(&mut self.text));
+        if !text.is_empty() {
+            self.blocks.push(ArticleBlock::Paragraph(text));
+        }
+    }
+
+    fn push_block(&mut self, block: ArticleBlock) {
+        self.flush();
+        self.blocks.push(block);
+    }
+
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    self.text.push_str(text);
+                }
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        if is_skipped(&element) {
+            return;
+        }
+
+        let name = element.value().name();
+        let text = || collapse_whitespace(&element.text().collect::<String>());
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                let text = text();
+                if !text.is_empty() {
+                    self.push_block(ArticleBlock::Heading { level, text });
+                }
+            }
+            "pre" => {
+                let code: String = element.text().collect();
+                let code = code.trim_matches('\n').to_string();
+                if !code.trim().is_empty() {
+                    self.push_block(ArticleBlock::Code(code));
+                }
+            }
+            "ul" | "ol" => self.list(element, 0),
+            "blockquote" => {
+                let text = text();
+                if !text.is_empty() {
+                    self.push_block(ArticleBlock::Quote(text));
+                }
+            }
+            "img" => {
+                let src = element
+                    .attr("src")
+                    .or_else(|| element.attr("data-src"))
+                    .and_then(|src| self.resolve(src));
+                if let Some(src) = src {
+                    let alt = element.attr("alt").unwrap_or("").trim().to_string();
+                    self.push_block(ArticleBlock::Image { src, alt });
+                }
+            }
+            "br" => self.text.push('\n'),
+            "a" | "abbr" | "b" | "cite" | "code" | "em" | "i" | "kbd" | "mark" | "q" | "s"
+            | "small" | "span" | "strong" | "sub" | "sup" | "time" | "u" => self.walk(element),
+            _ => {
+                self.flush();
+                self.walk(element);
+                self.flush();
+            }
+        }
+    }
+
+    /// Emits the list's own items, splitting it wherever an item holds a
+    /// nested list so each item appears once.
+    fn list(&mut self, list: ElementRef, depth: u8) {
+        let ordered = list.value().name() == "ol";
+        let mut items = Vec::new();
+
+        for item in list
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|child| child.value().name() == "li")
+        {
+            let mut text = String::new();
+            let mut nested = Vec::new();
+            item_text(item, &mut text, &mut nested);
+
+            let text = collapse_whitespace(&text);
+            if !text.is_empty() {
+                items.push(text);
+            }
+            for sublist in nested {
+                self.push_list(ordered, std::mem::take(&mut items), depth);
+                self.list(sublist, depth.saturating_add(1));
+            }
+        }
+
+        self.push_list(ordered, items, depth);
+    }
+
+    fn push_list(&mut self, ordered: bool, items: Vec<String>, depth: u8) {
+        if !items.is_empty() {
+            self.push_block(ArticleBlock::List {
+                ordered,
+                items,
+                depth,
+            });
+        }
+    }
+
+    /// Resolves an image link against the page, keeping only http(s) URLs.
+    fn resolve(&self, src: &str) -> Option<String> {
+        let url = match &self.base {
+            Some(base) => base.join(src).ok()?,
+            None => Url::parse(src).ok()?,
+        };
+        matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+    }
+}
+
+/// Collects a list item's text, setting aside the lists nested inside it.
+fn item_text<'a>(element: ElementRef<'a>, text: &mut String, nested: &mut Vec<ElementRef<'a>>) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                match child.value().name() {
+                    "ul" | "ol" => nested.push(child),
+                    _ if is_skipped(&child) => {}
+                    _ => item_text(child, text, nested),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::transport::FixtureTransport;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const URL: &str = "https://example.com/posts/hello";
+
+    fn paragraph(n: usize) -> String {
+        format!(
+            "<p>Paragraph {} has enough words, commas, and clauses to be scored as content.</p>",
+            n
+        )
+    }
+
+    #[test]
+    fn extract_keeps_the_main_content() {
+        let html = format!(
+            r#"<html><head><title>Fallback</title>
+            <meta property="og:title" content="Hello, world"></head>
+            <body>
+              <nav><p>Home, About, Contact, and a long list of links nobody reads.</p></nav>
+              <div class="post-content">
+                <h2>Intro</h2>
+                {}
+                <img src="/images/a.png" alt="A picture">
+                <pre>fn main() {{}}</pre>
+                <blockquote>Quoted   text</blockquote>
+                {}
+              </div>
+              <div class="sidebar">{}</div>
+            </body></html>"#,
+            paragraph(1),
+            paragraph(2),
+            paragraph(3),
+        );
+
+        let article = extract(&html, URL);
+
+        assert_eq!(article.title.as_deref(), Some("Hello, world"));
+        assert_eq!(
+            article.blocks,
+            vec![
+                ArticleBlock::Heading {
+                    level: 2,
+                    text: "Intro".to_string()
+                },
+                ArticleBlock::Paragraph(
+                    "Paragraph 1 has enough words, commas, and clauses to be scored as content."
+                        .to_string()
+                ),
+                ArticleBlock::Image {
+                    src: "https://example.com/images/a.png".to_string(),
+                    alt: "A picture".to_string()
+                },
+                ArticleBlock::Code("fn main() {}".to_string()),
+                ArticleBlock::Quote("Quoted text".to_string()),
+                ArticleBlock::Paragraph(
+                    "Paragraph 2 has enough words, commas, and clauses to be scored as content."
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_list_items_appear_once() {
+        let html = format!(
+            "<html><body><article>{}
+              <ul>
+                <li>One</li>
+                <li>Two
+                  <ol><li>Two A</li><li>Two B</li></ol>
+                </li>
+                <li>Three</li>
+              </ul>
+            </article></body></html>",
+            paragraph(1),
+        );
+
+        let article = extract(&html, URL);
+        let list = |ordered, items: &[&str], depth| ArticleBlock::List {
+            ordered,
+            items: items.iter().map(|i| i.to_string()).collect(),
+            depth,
+        };
+
+        assert_eq!(
+            article.blocks[1..],
+            [
+                list(false, &["One", "Two"], 0),
+                list(true, &["Two A", "Two B"], 1),
+                list(false, &["Three"], 0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_article_refuses_oversized_pages() {
+        let mut fixtures = FixtureTransport::default();
+        fixtures.insert(URL, "x".repeat(MAX_ARTICLE_BYTES + 1));
+        let client = HnClient::with_transport(&AppConfig::default(), Arc::new(fixtures));
+
+        let result = fetch_article(&client, URL).await;
+
+        assert!(matches!(
+            result,
+            Err(ApiError::TooLarge {
+                limit: MAX_ARTICLE_BYTES
+            })
+        ));
+    }
+
+    /// Serves each path's raw HTTP response from 127.0.0.1, one request per
+    /// connection, and returns the server's base URL.
+    async fn serve(routes: HashMap<&'static str, Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let routes = Arc::new(routes);
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let not_found = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec();
+                    let response = routes.get(path).unwrap_or(&not_found);
+                    // The client may hang up early on oversized bodies.
+                    let _ = socket.write_all(response).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        base
+    }
+
+    fn response(head: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\n\r\n", head).into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[tokio::test]
+    async fn http_transport_fetches_and_limits_pages() {
+        let page = format!(
+            "<html><body><article>{}</article></body></html>",
+            paragraph(1)
+        );
+        let oversized = vec![b'x'; MAX_ARTICLE_BYTES + 1];
+        let routes = HashMap::from([
+            (
+                "/page",
+                response(
+                    &format!(
+                        "200 OK\r\nContent-Type: text/html\r\nContent-Length: {}",
+                        page.len()
+                    ),
+                    page.as_bytes(),
+                ),
+            ),
+            // Refused from the header alone, before any body is read.
+            (
+                "/declared",
+                response(
+                    &format!("200 OK\r\nContent-Length: {}", oversized.len()),
+                    b"",
+                ),
+            ),
+            // No length header, so the body is counted as it arrives.
+            ("/undeclared", response("200 OK", &oversized)),
+            ("/gone", response("410 Gone\r\nContent-Length: 0", b"")),
+            (
+                "/busy",
+                response(
+                    "429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0",
+                    b"",
+                ),
+            ),
+        ]);
+        let base = serve(routes).await;
+        let client = HnClient::new(&AppConfig::default()).unwrap();
+
+        let article = fetch_article(&client, &format!("{}/page", base))
+            .await
+            .unwrap();
+        assert_eq!(
+            article.blocks,
+            vec![ArticleBlock::Paragraph(
+                "Paragraph 1 has enough words, commas, and clauses to be scored as content."
+                    .to_string()
+            )]
+        );
+
+        for path in ["/declared", "/undeclared"] {
+            let result = fetch_article(&client, &format!("{}{}", base, path)).await;
+            assert!(
+                matches!(
+                    result,
+                    Err(ApiError::TooLarge {
+                        limit: MAX_ARTICLE_BYTES
+                    })
+                ),
+                "{}: {:?}",
+                path,
+                result.map(|a| a.blocks)
+            );
+        }
+
+        let result = fetch_image(&client, &format!("{}/gone", base)).await;
+        assert!(matches!(result, Err(ApiError::Status(410))));
+
+        let result = fetch_image(&client, &format!("{}/busy", base)).await;
+        assert!(matches!(
+            result,
+            Err(ApiError::RateLimited {
+                retry_after: Some(0)
+            })
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum Category {
     #[default]
     Top,
@@ -32,6 +33,24 @@ impl Category {
         }
     }
 
+    /// The category whose `api_endpoint` is `endpoint`.
+    pub fn from_endpoint(endpoint: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.api_endpoint() == endpoint)
+    }
+
+    /// How long a cached snapshot of this category is shown without refetching.
+    pub fn cache_ttl(&self) -> Duration {
+        let minutes = match self {
+            Category::Top => 5,
+            Category::New => 2,
+            Category::Best => 30,
+            Category::Ask => 10,
+            Category::Show => 10,
+            Category::Jobs => 60,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             Category::Top => "Top",
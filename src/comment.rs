@@ -0,0 +1,73 @@
+use crate::{
+    html::{self, Block},
+    item::Item,
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct CommentNode {
+    pub comment: Item,
+    /// `comment.text` parsed once so rendering does not re-parse every frame.
+    pub body: Vec<Block>,
+    pub children: Vec<CommentNode>,
+}
+
+impl CommentNode {
+    /// Number of comments below this one, at any depth.
+    pub fn reply_count(&self) -> usize {
+        self.children.iter().map(|c| 1 + c.reply_count()).sum()
+    }
+}
+
+/// The discussion under an item: the item's own text plus its comment tree.
+pub struct CommentThread {
+    pub root_body: Vec<Block>,
+    /// The comment, story or poll the root item replies to or belongs to.
+    pub root_parent: Option<u64>,
+    /// Options of a poll, with their scores.
+    pub poll_options: Vec<Item>,
+    /// Direct replies to the root item and its total comment count, as
+    /// reported by the API when the thread was fetched.
+    pub root_kids: Vec<u64>,
+    pub descendants: Option<u64>,
+    pub comments: Vec<CommentNode>,
+    /// Number of comments that could not be fetched.
+    pub failed: usize,
+}
+
+impl CommentThread {
+    /// Assembles the tree from a flat map of fetched comments, keeping the
+    /// order of each item's `kids`. Missing comments are dropped with their subtrees.
+    pub fn build(root: Item, poll_options: Vec<Item>, mut fetched: HashMap<u64, Item>, failed: usize) -> Self {
+        let comments = Self::build_nodes(&root.kids, &mut fetched);
+        let root_body = root.text.as_deref().map(html::parse).unwrap_or_default();
+        Self {
+            root_body,
+            root_parent: root.parent.or(root.poll),
+            poll_options,
+            root_kids: root.kids,
+            descendants: root.descendants,
+            comments,
+            failed,
+        }
+    }
+
+    fn build_nodes(ids: &[u64], fetched: &mut HashMap<u64, Item>) -> Vec<CommentNode> {
+        let mut nodes = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let Some(comment) = fetched.remove(id) else {
+                continue;
+            };
+            let children = Self::build_nodes(&comment.kids, fetched);
+            let body = comment.text.as_deref().map(html::parse).unwrap_or_default();
+            nodes.push(CommentNode {
+                comment,
+                body,
+                children,
+            });
+        }
+
+        nodes
+    }
+}
@@ -1,4 +1,16 @@
-use crate::theme::GruvboxTheme;
+use crate::{
+    action::KeyBindings,
+    article::DEFAULT_ARTICLE_TIMEOUT_SECS,
+    hn_api::{
+        DEFAULT_API_BASE, DEFAULT_ITEM_TIMEOUT_SECS, DEFAULT_LIST_TIMEOUT_SECS,
+        DEFAULT_MAX_IN_FLIGHT, DEFAULT_MAX_RETRIES, DEFAULT_USER_AGENT,
+    },
+    listing::ListOptions,
+    mute::MuteRule,
+    search::DEFAULT_SEARCH_API_BASE,
+    theme::GruvboxTheme,
+    watch::WatchList,
+};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -6,12 +18,119 @@ use std::path::PathBuf;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub theme: GruvboxTheme,
+    /// Maximum number of item requests kept in flight while loading a category.
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+    /// How many times a request that failed with a transient error is retried.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Base URL of the HN Firebase API.
+    #[serde(default = "default_api_base")]
+    pub api_base: String,
+    /// Base URL of the Algolia HN Search API.
+    #[serde(default = "default_search_api_base")]
+    pub search_api_base: String,
+    /// `User-Agent` header sent with every request.
+    #[serde(default = "default_user_agent")]
+    pub user_agent: String,
+    /// Timeout for story id lists and search requests.
+    #[serde(default = "default_list_timeout_secs")]
+    pub list_timeout_secs: u64,
+    /// Timeout for individual item requests.
+    #[serde(default = "default_item_timeout_secs")]
+    pub item_timeout_secs: u64,
+    /// Timeout for downloading linked articles for archiving.
+    #[serde(default = "default_article_timeout_secs")]
+    pub article_timeout_secs: u64,
+    /// When set, API responses are served from this JSON file of
+    /// `{ "url": body }` fixtures instead of the network.
+    #[serde(default)]
+    pub fixtures_path: Option<PathBuf>,
+    /// Hide stories that have already been opened.
+    #[serde(default)]
+    pub hide_read: bool,
+    /// Download and store the linked article whenever a story is saved.
+    #[serde(default)]
+    pub archive_articles: bool,
+    /// Open story titles in the in-app reader instead of the browser.
+    #[serde(default = "default_open_in_reader")]
+    pub open_in_reader: bool,
+    /// Sort order and filters of the story list.
+    #[serde(default)]
+    pub list_options: ListOptions,
+    /// Keys for keyboard navigation, by action name.
+    #[serde(default)]
+    pub key_bindings: KeyBindings,
+    /// Names of the actions last run from the command palette, most recent
+    /// first.
+    #[serde(default)]
+    pub recent_commands: Vec<String>,
+    /// Authors and domains whose stories are badged and collected in the
+    /// Watching view.
+    #[serde(default)]
+    pub watch_list: WatchList,
+    /// Kill-file rules hiding stories from the story list.
+    #[serde(default)]
+    pub mute_rules: Vec<MuteRule>,
+}
+
+fn default_max_in_flight() -> usize {
+    DEFAULT_MAX_IN_FLIGHT
+}
+
+fn default_max_retries() -> u32 {
+    DEFAULT_MAX_RETRIES
+}
+
+fn default_api_base() -> String {
+    DEFAULT_API_BASE.to_string()
+}
+
+fn default_search_api_base() -> String {
+    DEFAULT_SEARCH_API_BASE.to_string()
+}
+
+fn default_user_agent() -> String {
+    DEFAULT_USER_AGENT.to_string()
+}
+
+fn default_list_timeout_secs() -> u64 {
+    DEFAULT_LIST_TIMEOUT_SECS
+}
+
+fn default_item_timeout_secs() -> u64 {
+    DEFAULT_ITEM_TIMEOUT_SECS
+}
+
+fn default_article_timeout_secs() -> u64 {
+    DEFAULT_ARTICLE_TIMEOUT_SECS
+}
+
+fn default_open_in_reader() -> bool {
+    true
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             theme: GruvboxTheme::Dark,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            api_base: default_api_base(),
+            search_api_base: default_search_api_base(),
+            user_agent: default_user_agent(),
+            list_timeout_secs: DEFAULT_LIST_TIMEOUT_SECS,
+            item_timeout_secs: DEFAULT_ITEM_TIMEOUT_SECS,
+            article_timeout_secs: DEFAULT_ARTICLE_TIMEOUT_SECS,
+            fixtures_path: None,
+            hide_read: false,
+            archive_articles: false,
+            open_in_reader: true,
+            list_options: ListOptions::default(),
+            key_bindings: KeyBindings::default(),
+            recent_commands: Vec::new(),
+            watch_list: WatchList::default(),
+            mute_rules: Vec::new(),
         }
     }
 }
@@ -26,6 +145,11 @@ impl Config {
         config_path.push("my_egui_pro");
         config_path.push("config.json");
 
+        Self::at(config_path)
+    }
+
+    /// A config stored at `config_path` instead of the data directory.
+    pub fn at(config_path: PathBuf) -> Self {
         Self { config_path }
     }
 
@@ -0,0 +1,356 @@
+//! Writes saved favorites out as JSON, CSV, a Markdown reading list or a
+//! Netscape bookmark file that browsers can import.
+
+use crate::storage::SavedFavorite;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Json,
+        ExportFormat::Csv,
+        ExportFormat::Markdown,
+        ExportFormat::Html,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Markdown => "Markdown reading list",
+            ExportFormat::Html => "Browser bookmarks (HTML)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// Parses a format name as given on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "html" | "htm" | "bookmarks" | "netscape" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_name(path.extension()?.to_str()?)
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "{}", e),
+            ExportError::Json(e) => write!(f, "JSON error: {}", e),
+            ExportError::Csv(e) => write!(f, "CSV error: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(err: serde_json::Error) -> Self {
+        ExportError::Json(err)
+    }
+}
+
+impl From<csv::Error> for ExportError {
+    fn from(err: csv::Error) -> Self {
+        ExportError::Csv(err)
+    }
+}
+
+/// One favorite as written to JSON and CSV. Tags are joined with `,` in CSV.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRecord {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub by: String,
+    #[serde(default)]
+    pub score: i32,
+    #[serde(default)]
+    pub time: u64,
+    #[serde(default)]
+    pub descendants: Option<u64>,
+    #[serde(default)]
+    pub saved_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+impl From<&SavedFavorite> for ExportRecord {
+    fn from(favorite: &SavedFavorite) -> Self {
+        let story = &favorite.story;
+        Self {
+            id: story.id,
+            title: story.title.clone(),
+            url: story.url.clone(),
+            by: story.by.clone(),
+            score: story.score,
+            time: story.time,
+            descendants: story.descendants,
+            saved_at: favorite.saved_at,
+            folder: favorite.folder.clone(),
+            tags: favorite.tags.clone(),
+            notes: favorite.notes.clone(),
+        }
+    }
+}
+
+/// Renders favorites in the given format.
+pub fn render(favorites: &[SavedFavorite], format: ExportFormat) -> Result<String, ExportError> {
+    match format {
+        ExportFormat::Json => {
+            let records: Vec<ExportRecord> = favorites.iter().map(ExportRecord::from).collect();
+            Ok(serde_json::to_string_pretty(&records)?)
+        }
+        ExportFormat::Csv => render_csv(favorites),
+        ExportFormat::Markdown => Ok(render_markdown(favorites)),
+        ExportFormat::Html => Ok(render_bookmarks(favorites)),
+    }
+}
+
+pub fn write(
+    path: &Path,
+    favorites: &[SavedFavorite],
+    format: ExportFormat,
+) -> Result<(), ExportError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, render(favorites, format)?)?;
+    Ok(())
+}
+
+/// Where exports started from the UI are written:
+/// `exports/favorites-<unix time>.<ext>` in the app's data directory.
+pub fn default_path(format: ExportFormat) -> std::path::PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    path.push("my_egui_pro");
+    path.push("exports");
+    path.push(format!(
+        "favorites-{}.{}",
+        crate::story::unix_now(),
+        format.extension()
+    ));
+    path
+}
+
+fn render_csv(favorites: &[SavedFavorite]) -> Result<String, ExportError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "id",
+        "title",
+        "url",
+        "by",
+        "score",
+        "time",
+        "descendants",
+        "saved_at",
+        "folder",
+        "tags",
+        "notes",
+    ])?;
+
+    for favorite in favorites {
+        let story = &favorite.story;
+        writer.write_record([
+            story.id.to_string(),
+            story.title.clone(),
+            story.url.clone().unwrap_or_default(),
+            story.by.clone(),
+            story.score.to_string(),
+            story.time.to_string(),
+            story.descendants.map(|d| d.to_string()).unwrap_or_default(),
+            favorite.saved_at.to_string(),
+            favorite.folder.clone().unwrap_or_default(),
+            favorite.tags.join(","),
+            favorite.notes.clone(),
+        ])?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn hn_url(id: u64) -> String {
+    format!("https://news.ycombinator.com/item?id={}", id)
+}
+
+/// Favorites grouped by folder, unfiled ones last.
+fn by_folder(favorites: &[SavedFavorite]) -> Vec<(Option<&str>, Vec<&SavedFavorite>)> {
+    let mut folders: BTreeMap<&str, Vec<&SavedFavorite>> = BTreeMap::new();
+    let mut unfiled = Vec::new();
+    for favorite in favorites {
+        match favorite.folder.as_deref() {
+            Some(folder) => folders.entry(folder).or_default().push(favorite),
+            None => unfiled.push(favorite),
+        }
+    }
+
+    let mut groups: Vec<_> = folders
+        .into_iter()
+        .map(|(name, items)| (Some(name), items))
+        .collect();
+    if !unfiled.is_empty() {
+        groups.push((None, unfiled));
+    }
+    groups
+}
+
+fn render_markdown(favorites: &[SavedFavorite]) -> String {
+    let mut out = String::from("# Hacker News Reading List\n");
+    let groups = by_folder(favorites);
+    let has_folders = groups.iter().any(|(name, _)| name.is_some());
+
+    for (folder, items) in groups {
+        if has_folders {
+            out.push_str(&format!("\n## {}\n", folder.unwrap_or("Unfiled")));
+        }
+        out.push('\n');
+
+        for favorite in items {
+            let story = &favorite.story;
+            let link = story.url.clone().unwrap_or_else(|| hn_url(story.id));
+            out.push_str(&format!(
+                "- [{}]({}) — {} points by {} · [discussion]({}) · saved {}\n",
+                escape_markdown(&story.title),
+                markdown_url(&link),
+                story.score,
+                story.by,
+                hn_url(story.id),
+                crate::story::date(favorite.saved_at),
+            ));
+
+            if !favorite.tags.is_empty() {
+                let tags: Vec<String> = favorite.tags.iter().map(|t| format!("`{}`", t)).collect();
+                out.push_str(&format!("  Tags: {}\n", tags.join(", ")));
+            }
+
+            for line in favorite.notes.trim().lines() {
+                out.push_str(&format!("  > {}\n", line));
+            }
+        }
+    }
+
+    out
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+/// Wraps a link destination in `<...>` so parentheses and spaces in it
+/// cannot end the link early. The few characters that cannot appear
+/// inside the brackets are percent-encoded.
+fn markdown_url(url: &str) -> String {
+    let url = url
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+        .replace('\n', "%0A");
+    format!("<{}>", url)
+}
+
+fn render_bookmarks(favorites: &[SavedFavorite]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n\
+         \x20   <DT><H3>Hacker News Favorites</H3>\n\
+         \x20   <DL><p>\n",
+    );
+
+    for (folder, items) in by_folder(favorites) {
+        let indent = if let Some(name) = folder {
+            out.push_str(&format!(
+                "        <DT><H3>{}</H3>\n        <DL><p>\n",
+                escape_html(name)
+            ));
+            "            "
+        } else {
+            "        "
+        };
+
+        for favorite in items {
+            let story = &favorite.story;
+            let link = story.url.clone().unwrap_or_else(|| hn_url(story.id));
+            out.push_str(&format!(
+                "{}<DT><A HREF=\"{}\" ADD_DATE=\"{}\"",
+                indent,
+                escape_html(&link),
+                favorite.saved_at
+            ));
+            if !favorite.tags.is_empty() {
+                out.push_str(&format!(
+                    " TAGS=\"{}\"",
+                    escape_html(&favorite.tags.join(","))
+                ));
+            }
+            out.push_str(&format!(">{}</A>\n", escape_html(&story.title)));
+
+            let mut description = format!("Discussion: {}", hn_url(story.id));
+            if !favorite.notes.trim().is_empty() {
+                description = format!("{}\n{}", favorite.notes.trim(), description);
+            }
+            out.push_str(&format!("{}<DD>{}\n", indent, escape_html(&description)));
+        }
+
+        if folder.is_some() {
+            out.push_str("        </DL><p>\n");
+        }
+    }
+
+    out.push_str("    </DL><p>\n</DL><p>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,77 +1,695 @@
-use crate::{category::Category, story::Story};
-use reqwest::{Client, Error as ReqwestError};
+use crate::{
+    category::Category,
+    comment::CommentThread,
+    config::AppConfig,
+    item::Item,
+    transport::{FixtureTransport, HttpTransport, Transport},
+    user::User,
+};
+use futures::stream::{self, StreamExt};
+use reqwest::{Error as ReqwestError, Url};
+use serde::de::DeserializeOwned;
 use serde_json::Error as JsonError;
+use std::collections::HashMap;
 use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
-const HN_API_BASE: &str = "https://hacker-news.firebaseio.com/v0";
-const STORY_LIMIT: usize = 30;
+pub const DEFAULT_API_BASE: &str = "https://hacker-news.firebaseio.com/v0";
+pub const DEFAULT_USER_AGENT: &str = concat!("my_egui_pro/", env!("CARGO_PKG_VERSION"));
+pub const DEFAULT_LIST_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_ITEM_TIMEOUT_SECS: u64 = 5;
+pub const PAGE_SIZE: usize = 30;
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(8);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ApiError {
+    /// The request did not complete within its timeout.
+    Timeout,
+    /// The server answered with a non-success HTTP status.
+    Status(u16),
+    /// The server answered 429, optionally telling us how long to wait.
+    RateLimited { retry_after: Option<u64> },
+    /// No connection could be established (offline, DNS, refused), or it
+    /// broke while the request was sent or the body was read.
+    ConnectionRefused(String),
+    /// The response body was not what we expected.
+    Decode {
+        item_id: Option<u64>,
+        message: String,
+    },
+    /// The response body was longer than the `limit` in bytes.
+    TooLarge { limit: usize },
+    /// Any other failure, such as a malformed URL or an unreadable
+    /// fixtures file. Retrying does not help.
     Request(String),
-    Parse(String),
+}
+
+impl ApiError {
+    /// Whether retrying the same request may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Timeout | ApiError::RateLimited { .. } | ApiError::ConnectionRefused(_) => {
+                true
+            }
+            ApiError::Status(code) => *code >= 500,
+            ApiError::Decode { .. } | ApiError::TooLarge { .. } | ApiError::Request(_) => false,
+        }
+    }
+
+    /// Attaches the item id to decode errors so the message can name the item.
+    pub fn for_item(self, id: u64) -> Self {
+        match self {
+            ApiError::Decode { message, .. } => ApiError::Decode {
+                item_id: Some(id),
+                message,
+            },
+            other => other,
+        }
+    }
+
+    /// A suggestion for the user on what to do about the error.
+    pub fn hint(&self) -> String {
+        match self {
+            ApiError::Timeout => {
+                "The server took too long to respond. Check your connection, or raise \
+                 `list_timeout_secs` / `item_timeout_secs` in config.json."
+                    .to_string()
+            }
+            ApiError::Status(code) if *code >= 500 => {
+                "The Hacker News API is having problems. Try again in a few minutes.".to_string()
+            }
+            ApiError::Status(_) => {
+                "The request was rejected. Check that `api_base` and `search_api_base` in config.json are correct."
+                    .to_string()
+            }
+            ApiError::RateLimited {
+                retry_after: Some(seconds),
+            } => format!(
+                "Too many requests. Wait {} seconds before retrying, or lower `max_in_flight` in config.json.",
+                seconds
+            ),
+            ApiError::RateLimited { retry_after: None } => {
+                "Too many requests. Wait a moment before retrying, or lower `max_in_flight` in config.json."
+                    .to_string()
+            }
+            ApiError::ConnectionRefused(_) => {
+                "Could not reach the server. Check your internet connection or proxy settings.".to_string()
+            }
+            ApiError::Decode { .. } => {
+                "The server sent an unexpected response. The API may have changed, or `api_base` points at \
+                 the wrong server."
+                    .to_string()
+            }
+            ApiError::TooLarge { .. } => {
+                "The page is too large to show in the reader. Open it in the browser instead.".to_string()
+            }
+            ApiError::Request(_) => {
+                "The request could not be made. Check `api_base`, `search_api_base` and `fixtures_path` in config.json."
+                    .to_string()
+            }
+        }
+    }
 }
 
 impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            ApiError::Timeout => write!(f, "Request timed out"),
+            ApiError::Status(code) => write!(f, "HTTP status {}", code),
+            ApiError::RateLimited { .. } => write!(f, "Rate limited (HTTP 429)"),
+            ApiError::ConnectionRefused(msg) => write!(f, "Connection failed: {}", msg),
+            ApiError::Decode {
+                item_id: Some(id),
+                message,
+            } => write!(f, "Could not decode item {}: {}", id, message),
+            ApiError::Decode {
+                item_id: None,
+                message,
+            } => write!(f, "Could not decode response: {}", message),
+            ApiError::TooLarge { limit } => {
+                write!(f, "Response is larger than {} MB", limit / (1024 * 1024))
+            }
             ApiError::Request(msg) => write!(f, "Request error: {}", msg),
-            ApiError::Parse(msg) => write!(f, "Parse error: {}", msg),
         }
     }
 }
 
 impl From<ReqwestError> for ApiError {
     fn from(err: ReqwestError) -> Self {
-        ApiError::Request(err.to_string())
+        if err.is_timeout() {
+            ApiError::Timeout
+        } else if let Some(status) = err.status() {
+            match status.as_u16() {
+                429 => ApiError::RateLimited { retry_after: None },
+                code => ApiError::Status(code),
+            }
+        } else if err.is_connect() || err.is_request() || err.is_body() {
+            ApiError::ConnectionRefused(err.to_string())
+        } else if err.is_decode() {
+            ApiError::Decode {
+                item_id: None,
+                message: err.to_string(),
+            }
+        } else {
+            ApiError::Request(err.to_string())
+        }
     }
 }
 
 impl From<JsonError> for ApiError {
     fn from(err: JsonError) -> Self {
-        ApiError::Parse(err.to_string())
+        ApiError::Decode {
+            item_id: None,
+            message: err.to_string(),
+        }
     }
 }
 
-pub async fn fetch_category(client: &Client, category: Category) -> Result<Vec<Story>, ApiError> {
-    let endpoint = format!("{}/{}.json", HN_API_BASE, category.api_endpoint());
+/// Exponential backoff with jitter for transient errors.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (0-based): `base * 2^attempt`,
+    /// capped at `max_delay`, plus up to 50% random jitter. A server-provided
+    /// `Retry-After` takes precedence.
+    fn delay(&self, attempt: u32, error: &ApiError) -> Duration {
+        if let ApiError::RateLimited {
+            retry_after: Some(seconds),
+        } = error
+        {
+            return Duration::from_secs(*seconds).min(self.max_delay);
+        }
 
-    let ids: Vec<u64> = client
-        .get(&endpoint)
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await?
-        .json()
-        .await?;
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        backoff + backoff.mul_f64(jitter_fraction() * 0.5)
+    }
+}
 
-    let ids_to_fetch = ids.into_iter().take(STORY_LIMIT).collect::<Vec<_>>();
+/// A pseudo-random value in `[0, 1)`, good enough to spread out retries.
+fn jitter_fraction() -> f64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    f64::from(nanos % 1_000_000) / 1_000_000.0
+}
 
-    let mut stories = Vec::with_capacity(ids_to_fetch.len());
+/// Handle to the HN Firebase and Algolia APIs. Cheap to clone; the base URLs,
+/// timeouts and concurrency come from `AppConfig` and requests go through a
+/// pluggable `Transport`.
+#[derive(Clone)]
+pub struct HnClient {
+    api_base: String,
+    search_api_base: String,
+    list_timeout: Duration,
+    item_timeout: Duration,
+    article_timeout: Duration,
+    max_in_flight: usize,
+    retry: RetryPolicy,
+    transport: Arc<dyn Transport>,
+}
 
-    for id in ids_to_fetch {
-        let item_endpoint = format!("{}/item/{}.json", HN_API_BASE, id);
+impl HnClient {
+    /// Builds a client for `config`, reading responses from `config.fixtures_path`
+    /// instead of the network when it is set.
+    pub fn new(config: &AppConfig) -> Result<Self, ApiError> {
+        let transport: Arc<dyn Transport> = match config.fixtures_path {
+            Some(ref path) => Arc::new(FixtureTransport::from_file(path)?),
+            None => Arc::new(HttpTransport::new(&config.user_agent)?),
+        };
 
-        match client
-            .get(&item_endpoint)
-            .timeout(std::time::Duration::from_secs(5))
-            .send()
+        Ok(Self::with_transport(config, transport))
+    }
+
+    pub fn with_transport(config: &AppConfig, transport: Arc<dyn Transport>) -> Self {
+        Self {
+            api_base: config.api_base.trim_end_matches('/').to_string(),
+            search_api_base: config.search_api_base.trim_end_matches('/').to_string(),
+            list_timeout: Duration::from_secs(config.list_timeout_secs),
+            item_timeout: Duration::from_secs(config.item_timeout_secs),
+            article_timeout: Duration::from_secs(config.article_timeout_secs),
+            max_in_flight: config.max_in_flight.max(1),
+            retry: RetryPolicy {
+                max_retries: config.max_retries,
+                base_delay: RETRY_BASE_DELAY,
+                max_delay: RETRY_MAX_DELAY,
+            },
+            transport,
+        }
+    }
+
+    pub fn search_api_base(&self) -> &str {
+        &self.search_api_base
+    }
+
+    /// GETs `url` with `query` appended and decodes the JSON body, retrying
+    /// transient failures according to the retry policy.
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+        timeout: Duration,
+    ) -> Result<T, ApiError> {
+        let url = if query.is_empty() {
+            url.to_string()
+        } else {
+            Url::parse_with_params(url, query)
+                .map_err(|e| ApiError::Request(format!("{}: {}", url, e)))?
+                .to_string()
+        };
+
+        let body = self.get_text(&url, timeout).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// GETs `url` and returns the body as text, retrying transient failures.
+    pub async fn get_text(&self, url: &str, timeout: Duration) -> Result<String, ApiError> {
+        self.retrying(|| self.transport.get(url, timeout)).await
+    }
+
+    /// GETs `url` and returns the raw body, retrying transient failures.
+    /// Bodies longer than `max_bytes` are refused.
+    pub async fn get_bytes(
+        &self,
+        url: &str,
+        timeout: Duration,
+        max_bytes: usize,
+    ) -> Result<Vec<u8>, ApiError> {
+        self.retrying(|| self.transport.get_bytes(url, timeout, max_bytes))
             .await
-        {
-            Ok(response) => {
-                if let Ok(story) = response.json().await {
-                    stories.push(story);
+    }
+
+    async fn retrying<T, F, Fut>(&self, mut request: F) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Ok(body) => return Ok(body),
+                Err(e) if e.is_transient() && attempt < self.retry.max_retries => {
+                    tokio::time::sleep(self.retry.delay(attempt, &e)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn list_timeout(&self) -> Duration {
+        self.list_timeout
+    }
+
+    /// Timeout for downloading linked article pages.
+    pub fn article_timeout(&self) -> Duration {
+        self.article_timeout
+    }
+}
+
+/// A batch of items fetched from a slice of the id list, in ranking order,
+/// plus the ids that could not be fetched or parsed.
+pub struct ItemPage {
+    pub items: Vec<Item>,
+    pub failures: Vec<(u64, ApiError)>,
+}
+
+impl ItemPage {
+    pub fn failure_summary(&self) -> Option<String> {
+        let (id, first_error) = self.failures.first()?;
+        Some(format!(
+            "{} of {} stories failed to load (item {}: {})",
+            self.failures.len(),
+            self.failures.len() + self.items.len(),
+            id,
+            first_error
+        ))
+    }
+}
+
+/// The full ranked id list of a category together with its first page.
+pub struct CategoryFetch {
+    pub ids: Vec<u64>,
+    pub first_page: ItemPage,
+}
+
+pub async fn fetch_category(
+    client: &HnClient,
+    category: Category,
+) -> Result<CategoryFetch, ApiError> {
+    let ids = fetch_story_ids(client, category).await?;
+    let first_page_len = ids.len().min(PAGE_SIZE);
+    let first_page = fetch_items(client, &ids[..first_page_len]).await;
+
+    Ok(CategoryFetch { ids, first_page })
+}
+
+pub async fn fetch_story_ids(client: &HnClient, category: Category) -> Result<Vec<u64>, ApiError> {
+    let endpoint = format!("{}/{}.json", client.api_base, category.api_endpoint());
+    client.get_json(&endpoint, &[], client.list_timeout).await
+}
+
+pub async fn fetch_items(client: &HnClient, ids: &[u64]) -> ItemPage {
+    // `buffered` keeps at most `max_in_flight` requests running and yields
+    // results in the order of the id list, so ranking is preserved.
+    let results: Vec<(u64, Result<Item, ApiError>)> = stream::iter(ids.iter().copied())
+        .map(|id| async move { (id, fetch_item(client, id).await) })
+        .buffered(client.max_in_flight)
+        .collect()
+        .await;
+
+    let mut items = Vec::with_capacity(results.len());
+    let mut failures = Vec::new();
+
+    for (id, result) in results {
+        match result {
+            Ok(item) => items.push(item),
+            Err(e) => failures.push((id, e)),
+        }
+    }
+
+    ItemPage { items, failures }
+}
+
+/// Fetches an item, its poll options if it is a poll, and its whole comment
+/// tree. Comments are fetched one depth level at a time so that at most
+/// `max_in_flight` requests are running.
+pub async fn fetch_comment_thread(
+    client: &HnClient,
+    story_id: u64,
+) -> Result<CommentThread, ApiError> {
+    let root = fetch_item(client, story_id).await?;
+
+    let poll_options: Vec<Item> = stream::iter(root.parts.iter().copied())
+        .map(|id| fetch_item(client, id))
+        .buffered(client.max_in_flight)
+        .filter_map(|result| async move { result.ok() })
+        .collect()
+        .await;
+
+    let mut fetched = HashMap::new();
+    let mut failed = 0;
+    let mut level = root.kids.clone();
+
+    while !level.is_empty() {
+        let results: Vec<(u64, Result<Item, ApiError>)> = stream::iter(level)
+            .map(|id| async move { (id, fetch_item(client, id).await) })
+            .buffer_unordered(client.max_in_flight)
+            .collect()
+            .await;
+
+        level = Vec::new();
+        for (id, result) in results {
+            match result {
+                Ok(comment) => {
+                    level.extend(comment.kids.iter().copied());
+                    fetched.insert(id, comment);
                 }
+                Err(_) => failed += 1,
             }
-            Err(_) => continue,
         }
     }
 
-    Ok(stories)
+    Ok(CommentThread::build(root, poll_options, fetched, failed))
+}
+
+pub async fn fetch_user(client: &HnClient, username: &str) -> Result<User, ApiError> {
+    let user_endpoint = format!("{}/user/{}.json", client.api_base, username);
+
+    // Like items, unknown users come back as `null`.
+    client
+        .get_json::<Option<User>>(&user_endpoint, &[], client.item_timeout)
+        .await?
+        .ok_or_else(|| ApiError::Decode {
+            item_id: None,
+            message: format!("user {} does not exist", username),
+        })
 }
 
-pub fn create_client() -> Client {
-    Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .unwrap()
+pub async fn fetch_item(client: &HnClient, id: u64) -> Result<Item, ApiError> {
+    let item_endpoint = format!("{}/item/{}.json", client.api_base, id);
+
+    // The API answers `null` for ids that do not exist.
+    client
+        .get_json::<Option<Item>>(&item_endpoint, &[], client.item_timeout)
+        .await
+        .map_err(|e| e.for_item(id))?
+        .ok_or_else(|| ApiError::Decode {
+            item_id: Some(id),
+            message: "item does not exist".to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TransportFuture;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    const API: &str = "https://hacker-news.firebaseio.com/v0";
+
+    fn item_json(id: u64) -> String {
+        format!(
+            r#"{{"id": {}, "type": "story", "title": "Story {}", "by": "pg", "time": 1}}"#,
+            id, id
+        )
+    }
+
+    fn client_with(transport: Arc<dyn Transport>, max_in_flight: usize) -> HnClient {
+        let config = AppConfig {
+            max_in_flight,
+            ..AppConfig::default()
+        };
+        let mut client = HnClient::with_transport(&config, transport);
+        // Keep retries fast.
+        client.retry.base_delay = Duration::from_millis(1);
+        client.retry.max_delay = Duration::from_millis(5);
+        client
+    }
+
+    /// Serves fixtures, answering later ids sooner, and records how many
+    /// requests were running at once.
+    struct SlowTransport {
+        fixtures: FixtureTransport,
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl Transport for SlowTransport {
+        fn get<'a>(&'a self, url: &'a str, timeout: Duration) -> TransportFuture<'a> {
+            Box::pin(async move {
+                let running = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(running, Ordering::SeqCst);
+                let id: u64 = url
+                    .trim_end_matches(".json")
+                    .rsplit('/')
+                    .next()
+                    .and_then(|id| id.parse().ok())
+                    .unwrap_or(0);
+                tokio::time::sleep(Duration::from_millis(20u64.saturating_sub(id))).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                self.fixtures.get(url, timeout).await
+            })
+        }
+
+        fn get_bytes<'a>(
+            &'a self,
+            url: &'a str,
+            timeout: Duration,
+            _max_bytes: usize,
+        ) -> TransportFuture<'a, Vec<u8>> {
+            Box::pin(async move { self.get(url, timeout).await.map(String::into_bytes) })
+        }
+    }
+
+    /// Fails with the queued errors first, then serves fixtures.
+    struct FlakyTransport {
+        fixtures: FixtureTransport,
+        errors: Mutex<Vec<ApiError>>,
+        attempts: AtomicUsize,
+    }
+
+    impl FlakyTransport {
+        fn new(fixtures: FixtureTransport, errors: Vec<ApiError>) -> Self {
+            Self {
+                fixtures,
+                errors: Mutex::new(errors),
+                attempts: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl Transport for FlakyTransport {
+        fn get<'a>(&'a self, url: &'a str, timeout: Duration) -> TransportFuture<'a> {
+            Box::pin(async move {
+                self.attempts.fetch_add(1, Ordering::SeqCst);
+                let error = {
+                    let mut errors = self.errors.lock().unwrap();
+                    (!errors.is_empty()).then(|| errors.remove(0))
+                };
+                match error {
+                    Some(error) => Err(error),
+                    None => self.fixtures.get(url, timeout).await,
+                }
+            })
+        }
+
+        fn get_bytes<'a>(
+            &'a self,
+            url: &'a str,
+            timeout: Duration,
+            _max_bytes: usize,
+        ) -> TransportFuture<'a, Vec<u8>> {
+            Box::pin(async move { self.get(url, timeout).await.map(String::into_bytes) })
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_items_keeps_order_within_max_in_flight() {
+        let ids: Vec<u64> = (1..=12).collect();
+        let mut fixtures = FixtureTransport::default();
+        for &id in &ids {
+            fixtures.insert(format!("{}/item/{}.json", API, id), item_json(id));
+        }
+        let transport = Arc::new(SlowTransport {
+            fixtures,
+            in_flight: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        });
+        let client = client_with(transport.clone(), 3);
+
+        let page = fetch_items(&client, &ids).await;
+
+        assert!(page.failures.is_empty());
+        assert_eq!(
+            page.items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            ids
+        );
+        assert!(transport.peak.load(Ordering::SeqCst) <= 3);
+        assert!(transport.peak.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn null_item_is_a_decode_error() {
+        let mut fixtures = FixtureTransport::default();
+        fixtures.insert(format!("{}/item/1.json", API), item_json(1));
+        fixtures.insert(format!("{}/item/2.json", API), "null");
+        let client = client_with(Arc::new(fixtures), 4);
+
+        let page = fetch_items(&client, &[1, 2]).await;
+
+        assert_eq!(page.items.len(), 1);
+        assert!(matches!(
+            page.failures.as_slice(),
+            [(
+                2,
+                ApiError::Decode {
+                    item_id: Some(2),
+                    ..
+                }
+            )]
+        ));
+        assert!(page
+            .failure_summary()
+            .unwrap()
+            .starts_with("1 of 2 stories failed"));
+    }
+
+    #[tokio::test]
+    async fn missing_fixture_is_a_404_and_not_retried() {
+        let transport = Arc::new(FlakyTransport::new(FixtureTransport::default(), Vec::new()));
+        let client = client_with(transport.clone(), 4);
+
+        let error = fetch_item(&client, 7).await.unwrap_err();
+
+        assert!(matches!(error, ApiError::Status(404)));
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn request_errors_are_not_retried() {
+        let mut fixtures = FixtureTransport::default();
+        fixtures.insert(format!("{}/item/1.json", API), item_json(1));
+        let errors = vec![ApiError::Request("bad fixtures file".to_string())];
+        let transport = Arc::new(FlakyTransport::new(fixtures, errors));
+        let client = client_with(transport.clone(), 4);
+
+        let error = fetch_item(&client, 1).await.unwrap_err();
+
+        assert!(matches!(error, ApiError::Request(_)));
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        let mut fixtures = FixtureTransport::default();
+        fixtures.insert(format!("{}/item/1.json", API), item_json(1));
+        let errors = vec![
+            ApiError::Status(503),
+            ApiError::RateLimited { retry_after: None },
+            ApiError::Status(500),
+        ];
+        let transport = Arc::new(FlakyTransport::new(fixtures, errors));
+        let client = client_with(transport.clone(), 4);
+
+        let item = fetch_item(&client, 1).await.unwrap();
+
+        assert_eq!(item.id, 1);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn retries_stop_after_max_retries() {
+        let errors = vec![ApiError::Status(502); 10];
+        let transport = Arc::new(FlakyTransport::new(FixtureTransport::default(), errors));
+        let client = client_with(transport.clone(), 4);
+
+        let error = fetch_item(&client, 1).await.unwrap_err();
+
+        assert!(matches!(error, ApiError::Status(502)));
+        assert_eq!(
+            transport.attempts.load(Ordering::SeqCst),
+            DEFAULT_MAX_RETRIES as usize + 1
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_and_honours_retry_after() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+        let error = ApiError::Status(503);
+
+        for (attempt, base) in [(0, 100), (1, 200), (2, 400), (3, 800), (4, 1000)] {
+            let delay = policy.delay(attempt, &error);
+            let base = Duration::from_millis(base);
+            assert!(
+                delay >= base && delay <= base + base / 2,
+                "attempt {}: {:?}",
+                attempt,
+                delay
+            );
+        }
+
+        let rate_limited = ApiError::RateLimited {
+            retry_after: Some(30),
+        };
+        assert_eq!(policy.delay(0, &rate_limited), Duration::from_millis(1000));
+    }
 }
@@ -0,0 +1,182 @@
+//! Minimal parser for the HTML subset Hacker News uses in item text:
+//! `<p>`, `<i>`, `<a href>`, `<pre><code>` and character entities.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Paragraph(Vec<Span>),
+    Code(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Span {
+    pub text: String,
+    pub italic: bool,
+    pub link: Option<String>,
+}
+
+#[derive(Default)]
+struct Parser {
+    blocks: Vec<Block>,
+    spans: Vec<Span>,
+    code: Option<String>,
+    italic: bool,
+    link: Option<String>,
+}
+
+impl Parser {
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(code) = self.code.as_mut() {
+            code.push_str(text);
+            return;
+        }
+
+        let text = text.replace('\n', " ");
+        match self.spans.last_mut() {
+            Some(last) if last.italic == self.italic && last.link == self.link => {
+                last.text.push_str(&text);
+            }
+            _ => self.spans.push(Span {
+                text,
+                italic: self.italic,
+                link: self.link.clone(),
+            }),
+        }
+    }
+
+    fn flush_paragraph(&mut self) {
+        let spans = std::mem::take(&mut self.spans);
+        if spans.iter().any(|s| !s.text.trim().is_empty()) {
+            self.blocks.push(Block::Paragraph(spans));
+        }
+    }
+
+    fn handle_tag(&mut self, tag: &str) {
+        let closing = tag.starts_with('/');
+        let body = tag.trim_start_matches('/');
+        let name = body
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+
+        match (name.as_str(), closing) {
+            ("p", false) => self.flush_paragraph(),
+            ("br", _) => self.push_text("\n"),
+            ("i" | "em", closing) => self.italic = !closing,
+            ("a", false) => self.link = attribute(body, "href").map(|href| decode_entities(&href)),
+            ("a", true) => self.link = None,
+            ("pre", false) => {
+                self.flush_paragraph();
+                self.code = Some(String::new());
+            }
+            ("pre", true) => {
+                if let Some(code) = self.code.take() {
+                    self.blocks.push(Block::Code(code.trim_end_matches('\n').to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Splits HN item HTML into paragraphs of styled spans and preformatted code blocks.
+pub fn parse(html: &str) -> Vec<Block> {
+    let mut parser = Parser::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        parser.push_text(&decode_entities(&rest[..start]));
+
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        parser.handle_tag(&rest[start + 1..start + end]);
+        rest = &rest[start + end + 1..];
+    }
+    parser.push_text(&decode_entities(rest));
+
+    if let Some(code) = parser.code.take() {
+        parser.blocks.push(Block::Code(code));
+    }
+    parser.flush_paragraph();
+    parser.blocks
+}
+
+/// Flattens item HTML to plain text, one paragraph per line.
+pub fn to_plain_text(html: &str) -> String {
+    parse(html)
+        .into_iter()
+        .map(|block| match block {
+            Block::Paragraph(spans) => spans.into_iter().map(|s| s.text).collect(),
+            Block::Code(code) => code,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &tag[start..];
+
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let value = &value[1..];
+            let end = value.find(quote)?;
+            Some(value[..end].to_string())
+        }
+        _ => Some(value.split_whitespace().next()?.to_string()),
+    }
+}
+
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,477 @@
+//! Reads favorites from our own JSON/CSV exports, browser bookmark files and
+//! saved copies of a user's HN favorites page, and resolves them to full
+//! items through the HN API before they are saved.
+
+use crate::export::ExportRecord;
+use crate::hn_api::{fetch_items, ApiError, HnClient};
+use crate::storage::SavedFavorite;
+use crate::story::{unix_now, Story};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static ITEM_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"news\.ycombinator\.com/item\?id=(\d+)").unwrap());
+
+/// A bookmark anchor and the `<DD>` description that may follow it.
+static ANCHOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?is)<a\s([^>]*)>(.*?)</a>(?:\s*<dd>([^<]*))?"#).unwrap());
+static HREF: LazyLock<Regex> = LazyLock::new(|| attribute_pattern("href"));
+static ID: LazyLock<Regex> = LazyLock::new(|| attribute_pattern("id"));
+static ADD_DATE: LazyLock<Regex> = LazyLock::new(|| attribute_pattern("add_date"));
+static TAGS: LazyLock<Regex> = LazyLock::new(|| attribute_pattern("tags"));
+
+static HN_ROW: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<tr[^>]*class=['"]athing[^'"]*['"][^>]*id=['"](\d+)['"]"#).unwrap()
+});
+static HN_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"href=['"](?:https?://news\.ycombinator\.com/)?item\?id=(\d+)"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    Json,
+    Csv,
+    Bookmarks,
+    HnFavorites,
+}
+
+impl ImportSource {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ImportSource::Json => "JSON export",
+            ImportSource::Csv => "CSV",
+            ImportSource::Bookmarks => "Browser bookmarks",
+            ImportSource::HnFavorites => "HN favorites page",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    /// The file parsed but contained no Hacker News item ids.
+    NoItems,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "{}", e),
+            ImportError::Json(e) => write!(f, "Not a valid favorites export: {}", e),
+            ImportError::Csv(e) => write!(f, "CSV error: {}", e),
+            ImportError::NoItems => write!(f, "No Hacker News items found in the file"),
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(err: std::io::Error) -> Self {
+        ImportError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(err: serde_json::Error) -> Self {
+        ImportError::Json(err)
+    }
+}
+
+impl From<csv::Error> for ImportError {
+    fn from(err: csv::Error) -> Self {
+        ImportError::Csv(err)
+    }
+}
+
+/// What will happen to one entry of the file when the import is committed.
+#[derive(Debug, Clone)]
+pub enum EntryStatus {
+    New,
+    /// Already a favorite; left untouched.
+    AlreadySaved,
+    /// The API could not resolve the id and the file has no title to fall back on.
+    Unresolved(ApiError),
+}
+
+#[derive(Debug, Clone)]
+pub struct PreviewEntry {
+    pub favorite: SavedFavorite,
+    pub status: EntryStatus,
+    /// Whether the entry will be imported; only `New` entries can be.
+    pub selected: bool,
+}
+
+/// The parsed and resolved contents of a file, shown before committing.
+#[derive(Debug, Clone)]
+pub struct ImportPreview {
+    pub source: ImportSource,
+    pub entries: Vec<PreviewEntry>,
+}
+
+impl ImportPreview {
+    pub fn selected(&self) -> Vec<SavedFavorite> {
+        self.entries
+            .iter()
+            .filter(|e| e.selected && matches!(e.status, EntryStatus::New))
+            .map(|e| e.favorite.clone())
+            .collect()
+    }
+
+    pub fn count(&self, predicate: impl Fn(&EntryStatus) -> bool) -> usize {
+        self.entries.iter().filter(|e| predicate(&e.status)).count()
+    }
+}
+
+/// Reads a file and works out what it is from its contents.
+pub fn parse_file(path: &Path) -> Result<(ImportSource, Vec<ExportRecord>), ImportError> {
+    let content = std::fs::read_to_string(path)?;
+    let (source, records) = parse(&content)?;
+
+    if records.is_empty() {
+        return Err(ImportError::NoItems);
+    }
+    Ok((source, records))
+}
+
+pub fn parse(content: &str) -> Result<(ImportSource, Vec<ExportRecord>), ImportError> {
+    let trimmed = content.trim_start();
+
+    if trimmed.starts_with('[') {
+        let records: Vec<ExportRecord> = serde_json::from_str(trimmed)?;
+        return Ok((ImportSource::Json, records));
+    }
+
+    if trimmed.starts_with('<') {
+        if trimmed
+            .to_ascii_uppercase()
+            .starts_with("<!DOCTYPE NETSCAPE-BOOKMARK-FILE")
+        {
+            return Ok((ImportSource::Bookmarks, parse_bookmarks(content)));
+        }
+        return Ok((ImportSource::HnFavorites, parse_hn_page(content)));
+    }
+
+    Ok((ImportSource::Csv, parse_csv(content)?))
+}
+
+fn item_link_id(text: &str) -> Option<u64> {
+    ITEM_LINK.captures(text)?[1].parse().ok()
+}
+
+fn record(id: u64) -> ExportRecord {
+    ExportRecord {
+        id,
+        title: String::new(),
+        url: None,
+        by: String::new(),
+        score: 0,
+        time: 0,
+        descendants: None,
+        saved_at: 0,
+        folder: None,
+        tags: Vec::new(),
+        notes: String::new(),
+    }
+}
+
+/// Reads rows by header name. Rows are matched by an `id` column, or else by
+/// any field holding an HN item link.
+fn parse_csv(content: &str) -> Result<Vec<ExportRecord>, ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_ascii_lowercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (id_col, title_col, url_col, by_col) =
+        (column("id"), column("title"), column("url"), column("by"));
+    let (score_col, time_col, descendants_col) =
+        (column("score"), column("time"), column("descendants"));
+    let (saved_col, folder_col, tags_col, notes_col) = (
+        column("saved_at"),
+        column("folder"),
+        column("tags"),
+        column("notes"),
+    );
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row?;
+        let field = |col: Option<usize>| col.and_then(|c| row.get(c)).unwrap_or("").trim();
+
+        let id = field(id_col)
+            .parse()
+            .ok()
+            .or_else(|| row.iter().find_map(item_link_id));
+        let Some(id) = id else {
+            continue;
+        };
+
+        let mut record = record(id);
+        record.title = field(title_col).to_string();
+        record.url = Some(field(url_col).to_string()).filter(|u| !u.is_empty());
+        record.by = field(by_col).to_string();
+        record.score = field(score_col).parse().unwrap_or(0);
+        record.time = field(time_col).parse().unwrap_or(0);
+        record.descendants = field(descendants_col).parse().ok();
+        record.saved_at = field(saved_col).parse().unwrap_or(0);
+        record.folder = Some(field(folder_col).to_string()).filter(|f| !f.is_empty());
+        record.tags = split_tags(field(tags_col));
+        record.notes = field(notes_col).to_string();
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn split_tags(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn attribute_pattern(name: &str) -> Regex {
+    Regex::new(&format!(r#"(?i)\b{}\s*=\s*"([^"]*)""#, name)).unwrap()
+}
+
+fn attribute(attrs: &str, pattern: &Regex) -> Option<String> {
+    pattern
+        .captures(attrs)
+        .map(|c| crate::html::decode_entities(&c[1]))
+}
+
+/// Picks out bookmarks pointing at HN discussions, keeping their title,
+/// `ADD_DATE`, `TAGS` and description. The discussion link is looked for in
+/// `HREF`, then in the `<DD>` description (where our own export puts it
+/// when `HREF` is the article), then in an `ID` attribute.
+fn parse_bookmarks(content: &str) -> Vec<ExportRecord> {
+    ANCHOR
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let attrs = &caps[1];
+            let href = attribute(attrs, &HREF);
+            let description = caps
+                .get(3)
+                .map(|d| crate::html::decode_entities(d.as_str().trim()))
+                .unwrap_or_default();
+
+            let linked = href.as_deref().and_then(item_link_id);
+            let id = linked.or_else(|| item_link_id(&description)).or_else(|| {
+                let id = attribute(attrs, &ID)?;
+                item_link_id(&id).or_else(|| id.trim().parse().ok())
+            })?;
+
+            let mut record = record(id);
+            record.title = crate::html::decode_entities(caps[2].trim());
+            if linked.is_none() {
+                record.url = href.filter(|h| h.starts_with("http"));
+            }
+            record.saved_at = attribute(attrs, &ADD_DATE)
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(0);
+            record.tags = attribute(attrs, &TAGS)
+                .map(|t| split_tags(&t))
+                .unwrap_or_default();
+            record.notes = description
+                .lines()
+                .filter(|line| !(line.starts_with("Discussion: ") && item_link_id(line).is_some()))
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string();
+            Some(record)
+        })
+        .collect()
+}
+
+/// Reads story rows (`<tr class="athing" id="…">`) from a saved HN
+/// favorites page, falling back to any `item?id=` links.
+fn parse_hn_page(content: &str) -> Vec<ExportRecord> {
+    let mut ids: Vec<u64> = HN_ROW
+        .captures_iter(content)
+        .filter_map(|c| c[1].parse().ok())
+        .collect();
+
+    if ids.is_empty() {
+        ids = HN_LINK
+            .captures_iter(content)
+            .filter_map(|c| c[1].parse().ok())
+            .collect();
+    }
+
+    ids.into_iter().map(record).collect()
+}
+
+/// Deduplicates records by id, looks every new one up through the API and
+/// marks what is already saved or could not be resolved.
+pub async fn resolve(
+    client: &HnClient,
+    source: ImportSource,
+    records: Vec<ExportRecord>,
+    existing: &HashSet<u64>,
+) -> ImportPreview {
+    let mut seen = HashSet::new();
+    let records: Vec<ExportRecord> = records.into_iter().filter(|r| seen.insert(r.id)).collect();
+
+    let new_ids: Vec<u64> = records
+        .iter()
+        .map(|r| r.id)
+        .filter(|id| !existing.contains(id))
+        .collect();
+    let page = fetch_items(client, &new_ids).await;
+    let mut items: HashMap<u64, Story> = page
+        .items
+        .into_iter()
+        .map(|item| (item.id, Story::from(item)))
+        .collect();
+    let mut failures: HashMap<u64, ApiError> = page.failures.into_iter().collect();
+
+    let now = unix_now();
+    let entries = records
+        .into_iter()
+        .map(|record| {
+            let id = record.id;
+            let resolved = items.remove(&id);
+            let status = if existing.contains(&id) {
+                EntryStatus::AlreadySaved
+            } else if resolved.is_some() || !record.title.is_empty() {
+                EntryStatus::New
+            } else {
+                EntryStatus::Unresolved(failures.remove(&id).unwrap_or(ApiError::Status(404)))
+            };
+
+            PreviewEntry {
+                selected: matches!(status, EntryStatus::New),
+                favorite: to_favorite(record, resolved, now),
+                status,
+            }
+        })
+        .collect();
+
+    ImportPreview { source, entries }
+}
+
+/// Prefers the freshly fetched story, keeping the file's own metadata.
+fn to_favorite(record: ExportRecord, resolved: Option<Story>, now: u64) -> SavedFavorite {
+    let story = resolved.unwrap_or_else(|| Story {
+        id: record.id,
+        kind: crate::item::ItemKind::Story,
+        title: if record.title.is_empty() {
+            format!("Item {}", record.id)
+        } else {
+            record.title.clone()
+        },
+        url: record.url.clone(),
+        by: record.by.clone(),
+        score: record.score,
+        time: record.time,
+        descendants: record.descendants,
+    });
+
+    SavedFavorite {
+        story,
+        saved_at: if record.saved_at > 0 {
+            record.saved_at
+        } else {
+            now
+        },
+        folder: record.folder,
+        tags: record.tags,
+        notes: record.notes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{render, ExportFormat};
+    use crate::item::ItemKind;
+
+    fn favorite(id: u64, url: Option<&str>) -> SavedFavorite {
+        SavedFavorite {
+            story: Story {
+                id,
+                kind: ItemKind::Story,
+                title: format!("Story <{}> & more", id),
+                url: url.map(str::to_string),
+                by: "pg".to_string(),
+                score: 42,
+                time: 1_700_000_000,
+                descendants: Some(7),
+            },
+            saved_at: 1_700_000_500,
+            folder: None,
+            tags: vec!["rust".to_string(), "egui".to_string()],
+            notes: "Read later\nsecond line".to_string(),
+        }
+    }
+
+    #[test]
+    fn bookmarks_export_round_trips() {
+        let favorites = vec![
+            favorite(1, Some("https://example.com/a?x=1&y=2")),
+            favorite(2, None),
+        ];
+        let html = render(&favorites, ExportFormat::Html).unwrap();
+
+        let (source, records) = parse(&html).unwrap();
+        assert_eq!(source, ImportSource::Bookmarks);
+        assert_eq!(records.len(), 2);
+
+        let article = &records[0];
+        assert_eq!(article.id, 1);
+        assert_eq!(article.title, "Story <1> & more");
+        assert_eq!(
+            article.url.as_deref(),
+            Some("https://example.com/a?x=1&y=2")
+        );
+        assert_eq!(article.saved_at, 1_700_000_500);
+        assert_eq!(article.tags, vec!["rust", "egui"]);
+        assert_eq!(article.notes, "Read later\nsecond line");
+
+        let discussion = &records[1];
+        assert_eq!(discussion.id, 2);
+        assert_eq!(discussion.url, None);
+    }
+
+    #[test]
+    fn bookmarks_match_an_id_attribute() {
+        let html = "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+                    <DL><p>\n\
+                    <DT><A HREF=\"https://example.com/\" ID=\"123\">Example</A>\n\
+                    <DT><A HREF=\"https://example.org/\">Unrelated</A>\n\
+                    <DD>Nothing to see here\n\
+                    </DL><p>\n";
+
+        let (_, records) = parse(html).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, 123);
+        assert_eq!(records[0].url.as_deref(), Some("https://example.com/"));
+    }
+
+    #[test]
+    fn csv_export_round_trips() {
+        let csv = render(
+            &[favorite(3, Some("https://example.com/"))],
+            ExportFormat::Csv,
+        )
+        .unwrap();
+
+        let (source, records) = parse(&csv).unwrap();
+        assert_eq!(source, ImportSource::Csv);
+        let record = &records[0];
+        assert_eq!(record.id, 3);
+        assert_eq!(record.score, 42);
+        assert_eq!(record.time, 1_700_000_000);
+        assert_eq!(record.descendants, Some(7));
+        assert_eq!(record.notes, "Read later\nsecond line");
+    }
+}
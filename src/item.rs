@@ -0,0 +1,112 @@
+use crate::html;
+use serde::{Deserialize, Serialize};
+
+const EXCERPT_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    #[default]
+    Story,
+    Comment,
+    Job,
+    Poll,
+    PollOpt,
+}
+
+impl ItemKind {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ItemKind::Story => "Story",
+            ItemKind::Comment => "Comment",
+            ItemKind::Job => "Job",
+            ItemKind::Poll => "Poll",
+            ItemKind::PollOpt => "Poll option",
+        }
+    }
+
+    /// The API's name for the kind, as stored in `favorites.kind`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ItemKind::Story => "story",
+            ItemKind::Comment => "comment",
+            ItemKind::Job => "job",
+            ItemKind::Poll => "poll",
+            ItemKind::PollOpt => "pollopt",
+        }
+    }
+
+    /// The kind named `name`; unknown names are treated as stories.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "comment" => ItemKind::Comment,
+            "job" => ItemKind::Job,
+            "poll" => ItemKind::Poll,
+            "pollopt" => ItemKind::PollOpt,
+            _ => ItemKind::Story,
+        }
+    }
+}
+
+/// Any item returned by the `/item/{id}.json` endpoint. Every field except
+/// `id` may be absent depending on the item type and whether it was deleted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Item {
+    pub id: u64,
+    #[serde(rename = "type", default)]
+    pub kind: ItemKind,
+    #[serde(default)]
+    pub by: Option<String>,
+    #[serde(default)]
+    pub time: u64,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub dead: bool,
+    #[serde(default)]
+    pub deleted: bool,
+    /// The parent comment or story of a comment.
+    #[serde(default)]
+    pub parent: Option<u64>,
+    /// The poll a poll option belongs to.
+    #[serde(default)]
+    pub poll: Option<u64>,
+    #[serde(default)]
+    pub kids: Vec<u64>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub score: Option<i32>,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// The options of a poll.
+    #[serde(default)]
+    pub parts: Vec<u64>,
+    #[serde(default)]
+    pub descendants: Option<u64>,
+}
+
+impl Item {
+    /// A one-line title for any item: the real title when there is one,
+    /// otherwise an excerpt of its text.
+    pub fn display_title(&self) -> String {
+        if let Some(ref title) = self.title {
+            return title.clone();
+        }
+
+        if self.deleted {
+            return "[deleted]".to_string();
+        }
+
+        let text = self.text.as_deref().map(html::to_plain_text).unwrap_or_default();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if text.is_empty() {
+            format!("[{}]", self.kind.display_name().to_lowercase())
+        } else if text.chars().count() > EXCERPT_CHARS {
+            format!("{}…", text.chars().take(EXCERPT_CHARS).collect::<String>())
+        } else {
+            text
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Sort orders and filters for the story list, applied on top of the order
+//! stories were fetched, saved or ranked in.
+
+use crate::story::StoryDisplay;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StorySort {
+    /// HN ranking when fetched, newest first when saved, relevance when
+    /// searching.
+    #[default]
+    Default,
+    Score,
+    Comments,
+    Newest,
+    Domain,
+    /// Most recently saved first; stories that are not saved come last.
+    Saved,
+}
+
+impl StorySort {
+    pub const ALL: [StorySort; 6] = [
+        StorySort::Default,
+        StorySort::Score,
+        StorySort::Comments,
+        StorySort::Newest,
+        StorySort::Domain,
+        StorySort::Saved,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            StorySort::Default => "Default order",
+            StorySort::Score => "Points",
+            StorySort::Comments => "Comments",
+            StorySort::Newest => "Newest",
+            StorySort::Domain => "Domain",
+            StorySort::Saved => "Recently saved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MaxAge {
+    #[default]
+    Any,
+    Hour,
+    SixHours,
+    Day,
+    ThreeDays,
+    Week,
+}
+
+impl MaxAge {
+    pub const ALL: [MaxAge; 6] = [
+        MaxAge::Any,
+        MaxAge::Hour,
+        MaxAge::SixHours,
+        MaxAge::Day,
+        MaxAge::ThreeDays,
+        MaxAge::Week,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            MaxAge::Any => "Any age",
+            MaxAge::Hour => "Past hour",
+            MaxAge::SixHours => "Past 6 hours",
+            MaxAge::Day => "Past day",
+            MaxAge::ThreeDays => "Past 3 days",
+            MaxAge::Week => "Past week",
+        }
+    }
+
+    fn seconds(&self) -> Option<u64> {
+        const HOUR: u64 = 60 * 60;
+        match self {
+            MaxAge::Any => None,
+            MaxAge::Hour => Some(HOUR),
+            MaxAge::SixHours => Some(6 * HOUR),
+            MaxAge::Day => Some(24 * HOUR),
+            MaxAge::ThreeDays => Some(3 * 24 * HOUR),
+            MaxAge::Week => Some(7 * 24 * HOUR),
+        }
+    }
+}
+
+/// Whether to show link posts, text posts (Ask HN and the like) or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PostKind {
+    #[default]
+    All,
+    Links,
+    Text,
+}
+
+impl PostKind {
+    pub const ALL: [PostKind; 3] = [PostKind::All, PostKind::Links, PostKind::Text];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PostKind::All => "Links and text",
+            PostKind::Links => "Links only",
+            PostKind::Text => "Text posts only",
+        }
+    }
+}
+
+/// The story list's sort order and filters, shared by all views.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListOptions {
+    pub sort: StorySort,
+    /// Flips the sort order.
+    pub reverse: bool,
+    pub min_score: i32,
+    pub min_comments: u64,
+    pub max_age: MaxAge,
+    pub kind: PostKind,
+}
+
+impl ListOptions {
+    pub fn has_filters(&self) -> bool {
+        self.min_score > 0
+            || self.min_comments > 0
+            || self.max_age != MaxAge::Any
+            || self.kind != PostKind::All
+    }
+
+    pub fn matches(&self, story: &StoryDisplay, now: u64) -> bool {
+        let story = &story.story;
+        let kind_ok = match self.kind {
+            PostKind::All => true,
+            PostKind::Links => story.url.is_some(),
+            PostKind::Text => story.url.is_none(),
+        };
+        let age_ok = self
+            .max_age
+            .seconds()
+            .is_none_or(|max| now.saturating_sub(story.time) <= max);
+
+        kind_ok
+            && age_ok
+            && story.score >= self.min_score
+            && story.descendants.unwrap_or(0) >= self.min_comments
+    }
+
+    /// Sorts `stories`, which are in the view's default order. Ties keep
+    /// that order, also when reversed. `saved_at` holds when each favorite
+    /// was saved.
+    pub fn sort<T>(
+        &self,
+        stories: &mut [T],
+        story: impl Fn(&T) -> &StoryDisplay,
+        saved_at: &HashMap<u64, u64>,
+    ) {
+        let compare = |a: &StoryDisplay, b: &StoryDisplay| -> Ordering {
+            let (sa, sb) = (&a.story, &b.story);
+            match self.sort {
+                StorySort::Default => Ordering::Equal,
+                StorySort::Score => sb.score.cmp(&sa.score),
+                StorySort::Comments => sb.descendants.cmp(&sa.descendants),
+                StorySort::Newest => sb.time.cmp(&sa.time),
+                // Text posts have no domain and go last.
+                StorySort::Domain => {
+                    (a.domain.is_none(), &a.domain).cmp(&(b.domain.is_none(), &b.domain))
+                }
+                StorySort::Saved => {
+                    let saved = |id| Reverse(saved_at.get(&id).copied().unwrap_or(0));
+                    saved(sa.id).cmp(&saved(sb.id))
+                }
+            }
+        };
+
+        if self.sort == StorySort::Default {
+            if self.reverse {
+                stories.reverse();
+            }
+            return;
+        }
+
+        stories.sort_by(|a, b| {
+            let ordering = compare(story(a), story(b));
+            if self.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
@@ -1,15 +1,39 @@
+mod action;
 mod app;
+mod article;
 mod category;
+mod comment;
 mod config;
+mod export;
 mod hn_api;
+mod html;
+mod import;
+mod item;
+mod listing;
+mod migrations;
+mod mute;
+mod palette;
+mod search;
 mod storage;
 mod story;
 mod theme;
+mod transport;
 mod ui;
+mod user;
+mod watch;
 
 use eframe::egui;
+use export::ExportFormat;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: my_egui_pro [--export <path> [--format json|csv|md|html]]";
 
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--export") {
+        std::process::exit(run_cli(&args));
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1000.0, 700.0]),
         ..Default::default()
@@ -18,6 +42,71 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Hacker News Reader",
         options,
-        Box::new(|_cc| Ok(Box::new(app::HackerNewsApp::new()))),
+        Box::new(|cc| {
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            Ok(Box::new(app::HackerNewsApp::new()))
+        }),
     )
 }
+
+/// Handles command-line invocations that run without opening the window,
+/// returning the process exit code.
+fn run_cli(args: &[String]) -> i32 {
+    let mut export_path = None;
+    let mut format = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--export" => export_path = args.next().map(PathBuf::from),
+            "--format" => match args.next().and_then(|name| ExportFormat::from_name(name)) {
+                Some(f) => format = Some(f),
+                None => {
+                    eprintln!("Unknown or missing export format\n{}", USAGE);
+                    return 2;
+                }
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return 0;
+            }
+            other => {
+                eprintln!("Unknown argument: {}\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let Some(path) = export_path else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(format) = format.or_else(|| ExportFormat::from_path(&path)) else {
+        eprintln!(
+            "Cannot tell the export format from {}; pass --format",
+            path.display()
+        );
+        return 2;
+    };
+
+    let favorites = match storage::FavoritesDB::new() {
+        Ok(db) => db.saved_favorites().map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let result = favorites.and_then(|favorites| {
+        export::write(&path, &favorites, format)
+            .map(|_| favorites.len())
+            .map_err(|e| e.to_string())
+    });
+
+    match result {
+        Ok(count) => {
+            println!("Exported {} favorites to {}", count, path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("Export failed: {}", e);
+            1
+        }
+    }
+}
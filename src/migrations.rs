@@ -0,0 +1,182 @@
+use crate::storage::StorageError;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have been applied. Never edit a released migration; append a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: favorites, response cache, read state and comment visits. Uses
+    // `IF NOT EXISTS` because databases from before migrations were tracked
+    // already contain some of these tables at version 0.
+    r#"
+CREATE TABLE IF NOT EXISTS favorites (
+    id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    url TEXT,
+    by TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    time INTEGER NOT NULL,
+    descendants INTEGER,
+    saved_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_time ON favorites(time DESC);
+
+CREATE TABLE IF NOT EXISTS cached_lists (
+    category TEXT PRIMARY KEY,
+    ids TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cached_items (
+    id INTEGER PRIMARY KEY,
+    json TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cached_items_fetched_at ON cached_items(fetched_at);
+
+CREATE TABLE IF NOT EXISTS read_items (
+    id INTEGER PRIMARY KEY,
+    read_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS story_visits (
+    id INTEGER PRIMARY KEY,
+    descendants INTEGER NOT NULL,
+    kids TEXT NOT NULL,
+    visited_at INTEGER NOT NULL
+);
+"#,
+    // 2: user-defined tags (many-to-many) and folders for favorites.
+    r#"
+CREATE TABLE folders (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+ALTER TABLE favorites ADD COLUMN folder_id INTEGER REFERENCES folders(id) ON DELETE SET NULL;
+
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+CREATE TABLE favorite_tags (
+    favorite_id INTEGER NOT NULL REFERENCES favorites(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (favorite_id, tag_id)
+);
+
+CREATE INDEX idx_favorite_tags_tag ON favorite_tags(tag_id);
+"#,
+    // 3: free-form markdown notes on favorites.
+    r#"
+ALTER TABLE favorites ADD COLUMN notes TEXT NOT NULL DEFAULT '';
+"#,
+    // 4: full-text index over favorites, kept in sync by triggers. The
+    // `article` column is filled in once archived article text exists.
+    r#"
+CREATE VIRTUAL TABLE favorites_fts USING fts5(title, url, author, notes, article);
+
+INSERT INTO favorites_fts (rowid, title, url, author, notes, article)
+SELECT id, title, COALESCE(url, ''), by, notes, '' FROM favorites;
+
+CREATE TRIGGER favorites_fts_insert AFTER INSERT ON favorites BEGIN
+    INSERT INTO favorites_fts (rowid, title, url, author, notes, article)
+    VALUES (new.id, new.title, COALESCE(new.url, ''), new.by, new.notes, '');
+END;
+
+CREATE TRIGGER favorites_fts_update AFTER UPDATE OF title, url, by, notes ON favorites BEGIN
+    UPDATE favorites_fts
+    SET title = new.title, url = COALESCE(new.url, ''), author = new.by, notes = new.notes
+    WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER favorites_fts_delete AFTER DELETE ON favorites BEGIN
+    DELETE FROM favorites_fts WHERE rowid = old.id;
+END;
+"#,
+    // 5: archived article content for favorites, zlib-compressed JSON.
+    r#"
+CREATE TABLE archives (
+    id INTEGER PRIMARY KEY REFERENCES favorites(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    content BLOB NOT NULL,
+    size INTEGER NOT NULL,
+    archived_at INTEGER NOT NULL
+);
+"#,
+    // 6: score, rank and comment count of each story every time a category
+    // is fetched.
+    r#"
+CREATE TABLE story_history (
+    story_id INTEGER NOT NULL,
+    category TEXT NOT NULL,
+    rank INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    comments INTEGER NOT NULL,
+    recorded_at INTEGER NOT NULL
+);
+CREATE INDEX story_history_story ON story_history (story_id, recorded_at);
+"#,
+    // 7: the item type of favorites, so saved jobs and polls stay what they
+    // are. Existing rows take it from the item cache where it is known.
+    r#"
+ALTER TABLE favorites ADD COLUMN kind TEXT NOT NULL DEFAULT 'story';
+
+UPDATE favorites
+SET kind = (SELECT json_extract(c.json, '$.type') FROM cached_items c WHERE c.id = favorites.id)
+WHERE (SELECT json_extract(c.json, '$.type') FROM cached_items c WHERE c.id = favorites.id) IS NOT NULL;
+"#,
+];
+
+/// Brings the database up to the latest schema version. Before upgrading a
+/// database that already holds data, a copy is written next to it as
+/// `favorites.db.v{N}.bak`. All pending migrations run in one transaction.
+pub fn run(conn: &mut Connection, db_path: &Path) -> Result<(), StorageError> {
+    let current: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    let latest = MIGRATIONS.len();
+
+    if current > latest {
+        return Err(StorageError::NewerSchema {
+            found: current,
+            supported: latest,
+        });
+    }
+
+    if current == latest {
+        return Ok(());
+    }
+
+    let table_count: i64 = conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'", [], |row| {
+        row.get(0)
+    })?;
+    if table_count > 0 {
+        backup(conn, &backup_path(db_path, current))?;
+    }
+
+    let tx = conn.transaction()?;
+    for migration in &MIGRATIONS[current..] {
+        tx.execute_batch(migration)?;
+    }
+    tx.pragma_update(None, "user_version", latest)?;
+    tx.commit()?;
+
+    Ok(())
+}
+
+fn backup_path(db_path: &Path, version: usize) -> PathBuf {
+    let mut file_name = db_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".v{}.bak", version));
+    db_path.with_file_name(file_name)
+}
+
+fn backup(conn: &Connection, path: &Path) -> Result<(), StorageError> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! The kill file: rules that hide stories from the story list by domain,
+//! author or a regex over the title.
+
+use crate::story::{domain_matches, StoryDisplay};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MuteKind {
+    #[default]
+    Domain,
+    Author,
+    /// A case-insensitive regular expression matched anywhere in the title.
+    Title,
+}
+
+impl MuteKind {
+    pub const ALL: [MuteKind; 3] = [MuteKind::Domain, MuteKind::Author, MuteKind::Title];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            MuteKind::Domain => "Domain",
+            MuteKind::Author => "Author",
+            MuteKind::Title => "Title regex",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MuteRule {
+    pub kind: MuteKind,
+    pub pattern: String,
+}
+
+impl MuteRule {
+    pub fn description(&self) -> String {
+        format!("{}: {}", self.kind.display_name(), self.pattern)
+    }
+}
+
+/// Mute rules ready for matching, with title patterns compiled once.
+/// Rules whose pattern is blank or does not compile match nothing.
+#[derive(Debug, Default)]
+pub struct KillFile {
+    rules: Vec<(MuteRule, Option<Regex>)>,
+}
+
+impl KillFile {
+    pub fn new(rules: &[MuteRule]) -> Self {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let regex = match rule.kind {
+                    MuteKind::Title => compile(&rule.pattern).ok(),
+                    _ => None,
+                };
+                (rule.clone(), regex)
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The first rule that mutes `story`, if any.
+    pub fn matching_rule(&self, story: &StoryDisplay) -> Option<&MuteRule> {
+        self.rules
+            .iter()
+            .find(|(rule, regex)| {
+                let pattern = rule.pattern.trim();
+                !pattern.is_empty()
+                    && match rule.kind {
+                        MuteKind::Domain => story
+                            .domain
+                            .as_deref()
+                            .is_some_and(|host| domain_matches(host, pattern)),
+                        MuteKind::Author => story.story.by.eq_ignore_ascii_case(pattern),
+                        MuteKind::Title => regex
+                            .as_ref()
+                            .is_some_and(|regex| regex.is_match(&story.story.title)),
+                    }
+            })
+            .map(|(rule, _)| rule)
+    }
+
+    pub fn is_muted(&self, story: &StoryDisplay) -> bool {
+        self.matching_rule(story).is_some()
+    }
+}
+
+/// Compiles a title pattern, for reporting invalid ones while editing.
+pub fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern.trim())
+        .case_insensitive(true)
+        .build()
+}
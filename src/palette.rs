@@ -0,0 +1,104 @@
+//! Fuzzy matching and ranking for the command palette.
+
+use crate::action::Action;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// How many recently run commands are remembered for ranking.
+pub const MAX_RECENT: usize = 8;
+
+static ITEM_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:#|.*item\?id=)?(\d+)$").unwrap());
+
+/// An entry of the palette: a registered action, or a search for the text
+/// typed into it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Action(Action),
+    Search(String),
+}
+
+impl Command {
+    pub fn title(&self) -> String {
+        match self {
+            Command::Action(action) => action.description(),
+            Command::Search(text) => format!("Search Hacker News for “{}”", text),
+        }
+    }
+}
+
+/// Commands matching `query`, best first. `recent` holds the names of
+/// recently run actions, most recent first; they rank higher and lead the
+/// list while nothing is typed. An item id or link offers to open it.
+pub fn commands(query: &str, recent: &[String]) -> Vec<Command> {
+    let query = query.trim();
+    let recency = |action: &Action| {
+        let name = action.name();
+        recent
+            .iter()
+            .position(|r| *r == name)
+            .map_or(0, |i| (MAX_RECENT - i.min(MAX_RECENT)) as i32 * 3)
+    };
+
+    let mut scored: Vec<(i32, Action)> = Action::ALL
+        .iter()
+        .filter(|action| action.in_palette())
+        .filter_map(|&action| {
+            let score = if query.is_empty() {
+                0
+            } else {
+                fuzzy_score(query, &action.description())?
+            };
+            Some((score + recency(&action), action))
+        })
+        .collect();
+    // Stable, so equal scores keep registry order.
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    let mut commands = Vec::new();
+    if let Some(id) = item_id(query) {
+        commands.push(Command::Action(Action::OpenItem(id)));
+    }
+    commands.extend(
+        scored
+            .into_iter()
+            .map(|(_, action)| Command::Action(action)),
+    );
+    if !query.is_empty() {
+        commands.push(Command::Search(query.to_string()));
+    }
+    commands
+}
+
+/// An item id typed as `123`, `#123` or an HN item link.
+fn item_id(query: &str) -> Option<u64> {
+    ITEM_ID.captures(query)?[1].parse().ok()
+}
+
+/// Scores `text` against `query` when every query character appears in it in
+/// order, ignoring case. Consecutive matches and matches at word starts score
+/// higher; skipped characters cost a little.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + text[next..].iter().position(|&c| c == wanted)?;
+
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (found - next).min(3) as i32;
+
+        previous = Some(found);
+        next = found + 1;
+    }
+
+    Some(score)
+}
@@ -0,0 +1,333 @@
+use crate::{
+    hn_api::{ApiError, HnClient},
+    item::{Item, ItemKind},
+    story::{unix_now, Story},
+};
+use serde::Deserialize;
+
+pub const DEFAULT_SEARCH_API_BASE: &str = "https://hn.algolia.com/api/v1";
+const HITS_PER_PAGE: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTag {
+    Story,
+    Comment,
+    AskHn,
+    ShowHn,
+}
+
+impl SearchTag {
+    pub const ALL: [SearchTag; 4] = [SearchTag::Story, SearchTag::Comment, SearchTag::AskHn, SearchTag::ShowHn];
+
+    pub fn api_tag(&self) -> &'static str {
+        match self {
+            SearchTag::Story => "story",
+            SearchTag::Comment => "comment",
+            SearchTag::AskHn => "ask_hn",
+            SearchTag::ShowHn => "show_hn",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SearchTag::Story => "Stories",
+            SearchTag::Comment => "Comments",
+            SearchTag::AskHn => "Ask HN",
+            SearchTag::ShowHn => "Show HN",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateRange {
+    #[default]
+    AllTime,
+    PastDay,
+    PastWeek,
+    PastMonth,
+    PastYear,
+}
+
+impl DateRange {
+    pub const ALL: [DateRange; 5] = [
+        DateRange::AllTime,
+        DateRange::PastDay,
+        DateRange::PastWeek,
+        DateRange::PastMonth,
+        DateRange::PastYear,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            DateRange::AllTime => "All time",
+            DateRange::PastDay => "Past day",
+            DateRange::PastWeek => "Past week",
+            DateRange::PastMonth => "Past month",
+            DateRange::PastYear => "Past year",
+        }
+    }
+
+    fn seconds(&self) -> Option<u64> {
+        const DAY: u64 = 24 * 60 * 60;
+        match self {
+            DateRange::AllTime => None,
+            DateRange::PastDay => Some(DAY),
+            DateRange::PastWeek => Some(7 * DAY),
+            DateRange::PastMonth => Some(30 * DAY),
+            DateRange::PastYear => Some(365 * DAY),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchSort {
+    #[default]
+    Relevance,
+    Date,
+}
+
+impl SearchSort {
+    fn endpoint(&self) -> &'static str {
+        match self {
+            SearchSort::Relevance => "search",
+            SearchSort::Date => "search_by_date",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub text: String,
+    pub tag: Option<SearchTag>,
+    pub author: String,
+    pub min_points: u32,
+    pub date_range: DateRange,
+    pub sort: SearchSort,
+}
+
+impl SearchQuery {
+    fn params(&self, now: u64) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("query", self.text.trim().to_string()),
+            ("hitsPerPage", HITS_PER_PAGE.to_string()),
+        ];
+
+        let mut tags = Vec::new();
+        if let Some(tag) = self.tag {
+            tags.push(tag.api_tag().to_string());
+        }
+        let author = self.author.trim();
+        if !author.is_empty() {
+            tags.push(format!("author_{}", author));
+        }
+        if !tags.is_empty() {
+            params.push(("tags", tags.join(",")));
+        }
+
+        let mut numeric_filters = Vec::new();
+        if self.min_points > 0 {
+            numeric_filters.push(format!("points>={}", self.min_points));
+        }
+        if let Some(seconds) = self.date_range.seconds() {
+            numeric_filters.push(format!("created_at_i>{}", now.saturating_sub(seconds)));
+        }
+        if !numeric_filters.is_empty() {
+            params.push(("numericFilters", numeric_filters.join(",")));
+        }
+
+        params
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    hits: Vec<SearchHit>,
+}
+
+#[derive(Deserialize)]
+struct SearchHit {
+    #[serde(rename = "objectID")]
+    object_id: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    points: Option<i32>,
+    #[serde(default)]
+    num_comments: Option<u64>,
+    #[serde(default)]
+    created_at_i: u64,
+    #[serde(default)]
+    story_text: Option<String>,
+    #[serde(default)]
+    comment_text: Option<String>,
+    #[serde(default, rename = "_tags")]
+    tags: Vec<String>,
+}
+
+impl SearchHit {
+    fn into_item(self) -> Option<Item> {
+        let has_tag = |tag: &str| self.tags.iter().any(|t| t == tag);
+        let kind = if has_tag("comment") {
+            ItemKind::Comment
+        } else if has_tag("job") {
+            ItemKind::Job
+        } else if has_tag("poll") {
+            ItemKind::Poll
+        } else if has_tag("pollopt") {
+            ItemKind::PollOpt
+        } else {
+            ItemKind::Story
+        };
+
+        Some(Item {
+            id: self.object_id.parse().ok()?,
+            kind,
+            by: self.author,
+            time: self.created_at_i,
+            text: self.comment_text.or(self.story_text),
+            url: self.url.filter(|url| !url.is_empty()),
+            score: self.points,
+            title: self.title,
+            descendants: self.num_comments,
+            ..Default::default()
+        })
+    }
+}
+
+/// Queries the Algolia HN Search API and converts the hits to stories.
+pub async fn search(client: &HnClient, query: &SearchQuery) -> Result<Vec<Story>, ApiError> {
+    let endpoint = format!("{}/{}", client.search_api_base(), query.sort.endpoint());
+    let now = unix_now();
+
+    let response: SearchResponse = client
+        .get_json(&endpoint, &query.params(now), client.list_timeout())
+        .await?;
+
+    Ok(response
+        .hits
+        .into_iter()
+        .filter_map(SearchHit::into_item)
+        .map(Story::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::AppConfig, story::StoryDisplay, transport::FixtureTransport};
+    use std::sync::Arc;
+
+    fn client(transport: FixtureTransport) -> HnClient {
+        HnClient::with_transport(&AppConfig::default(), Arc::new(transport))
+    }
+
+    #[test]
+    fn params_combine_tags_and_numeric_filters() {
+        let query = SearchQuery {
+            text: "  rust  ".to_string(),
+            tag: Some(SearchTag::ShowHn),
+            author: "pg".to_string(),
+            min_points: 100,
+            date_range: DateRange::PastDay,
+            sort: SearchSort::Date,
+        };
+
+        assert_eq!(
+            query.params(1_000_000),
+            vec![
+                ("query", "rust".to_string()),
+                ("hitsPerPage", "50".to_string()),
+                ("tags", "show_hn,author_pg".to_string()),
+                (
+                    "numericFilters",
+                    "points>=100,created_at_i>913600".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn params_leave_out_unused_filters() {
+        let query = SearchQuery {
+            text: "rust".to_string(),
+            ..Default::default()
+        };
+
+        let params = query.params(1_000_000);
+        assert!(params
+            .iter()
+            .all(|(name, _)| *name != "tags" && *name != "numericFilters"));
+    }
+
+    #[tokio::test]
+    async fn search_by_date_maps_hits_to_stories() {
+        let mut transport = FixtureTransport::default();
+        transport.insert(
+            "https://hn.algolia.com/api/v1/search_by_date?query=rust&hitsPerPage=50\
+             &tags=story%2Cauthor_pg&numericFilters=points%3E%3D10",
+            r#"{"hits": [
+                {"objectID": "42", "title": "Rust 2.0", "url": "https://example.com/rust",
+                 "author": "pg", "points": 120, "num_comments": 33, "created_at_i": 1700000000,
+                 "_tags": ["story", "author_pg"]},
+                {"objectID": "43", "title": "Ask HN: Rust?", "url": "", "author": "pg",
+                 "points": 12, "created_at_i": 1700000100, "_tags": ["story", "ask_hn"]},
+                {"objectID": "44", "comment_text": "<p>Nice</p>", "author": "pg",
+                 "created_at_i": 1700000200, "_tags": ["comment"]},
+                {"objectID": "not-a-number", "title": "Broken"}
+            ]}"#,
+        );
+        let query = SearchQuery {
+            text: "rust".to_string(),
+            tag: Some(SearchTag::Story),
+            author: "pg".to_string(),
+            min_points: 10,
+            sort: SearchSort::Date,
+            ..Default::default()
+        };
+
+        let stories: Vec<StoryDisplay> = search(&client(transport), &query)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(StoryDisplay::from_story)
+            .collect();
+
+        assert_eq!(stories.len(), 3);
+        let first = &stories[0];
+        assert_eq!(first.story.id, 42);
+        assert_eq!(first.story.kind, ItemKind::Story);
+        assert_eq!(first.story.title, "Rust 2.0");
+        assert_eq!(first.story.by, "pg");
+        assert_eq!(first.story.score, 120);
+        assert_eq!(first.story.time, 1_700_000_000);
+        assert_eq!(first.story.descendants, Some(33));
+        assert_eq!(first.domain.as_deref(), Some("example.com"));
+
+        // An empty url means a text post.
+        assert_eq!(stories[1].story.url, None);
+        assert_eq!(stories[1].domain, None);
+
+        assert_eq!(stories[2].story.kind, ItemKind::Comment);
+        assert_eq!(stories[2].story.title, "Nice");
+        assert_eq!(stories[2].story.score, 0);
+    }
+
+    #[tokio::test]
+    async fn relevance_sort_uses_the_search_endpoint() {
+        let mut transport = FixtureTransport::default();
+        transport.insert(
+            "https://hn.algolia.com/api/v1/search?query=rust&hitsPerPage=50",
+            r#"{"hits": []}"#,
+        );
+        let query = SearchQuery {
+            text: "rust".to_string(),
+            ..Default::default()
+        };
+
+        assert!(search(&client(transport), &query).await.unwrap().is_empty());
+    }
+}
@@ -1,36 +1,138 @@
-use crate::story::Story;
-use rusqlite::{Connection, Result as SqliteResult};
-use std::path::PathBuf;
-
-const SCHEMA: &str = r#"
-CREATE TABLE IF NOT EXISTS favorites (
-    id INTEGER PRIMARY KEY,
-    title TEXT NOT NULL,
-    url TEXT,
-    by TEXT NOT NULL,
-    score INTEGER NOT NULL,
-    time INTEGER NOT NULL,
-    descendants INTEGER,
-    saved_at INTEGER NOT NULL
-);
-
-CREATE INDEX IF NOT EXISTS idx_time ON favorites(time DESC);
-"#;
+use crate::{
+    article::Article,
+    category::Category,
+    item::{Item, ItemKind},
+    migrations,
+    story::{unix_now, Story},
+};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum StorageError {
+    Sqlite(rusqlite::Error),
+    Io(std::io::Error),
+    /// The database was written by a newer build with migrations we do not know.
+    NewerSchema { found: usize, supported: usize },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Sqlite(e) => write!(f, "SQLite error: {}", e),
+            StorageError::Io(e) => write!(f, "I/O error: {}", e),
+            StorageError::NewerSchema { found, supported } => write!(
+                f,
+                "favorites.db has schema version {}, but this build only supports up to version {}. \
+                 Update my_egui_pro, or restore a backup made by this version.",
+                found, supported
+            ),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(err: rusqlite::Error) -> Self {
+        StorageError::Sqlite(err)
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+/// A category id list as it was last fetched.
+pub struct CachedList {
+    pub ids: Vec<u64>,
+    pub fetched_at: u64,
+}
+
+/// What a story's discussion looked like the last time its comments were opened.
+#[derive(Debug, Clone)]
+pub struct Visit {
+    pub descendants: u64,
+    pub kids: HashSet<u64>,
+    pub visited_at: u64,
+}
+
+impl Visit {
+    /// Whether a comment appeared after this visit. Top-level comments are
+    /// also checked against the remembered kids in case of clock skew.
+    pub fn is_new_comment(&self, comment: &Item, top_level: bool) -> bool {
+        comment.time > self.visited_at || (top_level && !self.kids.contains(&comment.id))
+    }
+
+    /// How many comments were added since this visit.
+    pub fn new_comments(&self, descendants: u64) -> u64 {
+        descendants.saturating_sub(self.descendants)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Folder {
+    pub id: i64,
+    pub name: String,
+    pub count: usize,
+}
+
+/// A favorite with everything saved alongside it, as exported.
+#[derive(Debug, Clone)]
+pub struct SavedFavorite {
+    pub story: Story,
+    pub saved_at: u64,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: String,
+}
+
+/// Markers `search_favorites` wraps around matched terms.
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+
+/// A favorite matched by full-text search, best match first.
+#[derive(Debug, Clone)]
+pub struct FavoriteMatch {
+    pub id: u64,
+    /// The title with matched terms wrapped in `MATCH_START`/`MATCH_END`.
+    pub title: String,
+    /// An excerpt of the best matching column other than the title, if any.
+    pub snippet: Option<String>,
+}
+
+/// Where a story stood in a category at one fetch.
+#[derive(Debug, Clone)]
+pub struct HistoryPoint {
+    pub category: Category,
+    /// 1-based position in the category's ranking.
+    pub rank: usize,
+    pub score: i32,
+    pub comments: u64,
+    pub recorded_at: u64,
+}
 
 pub struct FavoritesDB {
     conn: Connection,
 }
 
 impl FavoritesDB {
-    pub fn new() -> SqliteResult<Self> {
-        let db_path = Self::db_path();
+    pub fn new() -> Result<Self, StorageError> {
+        Self::open(&Self::db_path())
+    }
+
+    /// Opens the database at `db_path`, creating and migrating it as needed.
+    pub fn open(db_path: &Path) -> Result<Self, StorageError> {
         let parent_dir = db_path.parent().unwrap();
-        std::fs::create_dir_all(parent_dir).map_err(|e| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(e))
-        })?;
+        std::fs::create_dir_all(parent_dir)?;
 
-        let conn = Connection::open(db_path)?;
-        conn.execute_batch(SCHEMA)?;
+        let mut conn = Connection::open(db_path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrations::run(&mut conn, db_path)?;
 
         Ok(Self { conn })
     }
@@ -49,8 +151,16 @@ impl FavoritesDB {
             .as_secs() as i64;
 
         self.conn.execute(
-            "INSERT OR REPLACE INTO favorites (id, title, url, by, score, time, descendants, saved_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO favorites (id, title, url, by, score, time, descendants, saved_at, kind)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+                kind = excluded.kind,
+                title = excluded.title,
+                url = excluded.url,
+                by = excluded.by,
+                score = excluded.score,
+                time = excluded.time,
+                descendants = excluded.descendants",
             (
                 &(story.id as i64),
                 &story.title,
@@ -60,6 +170,7 @@ impl FavoritesDB {
                 &(story.time as i64),
                 &story.descendants,
                 &saved_at,
+                story.kind.name(),
             ),
         )?;
 
@@ -79,7 +190,7 @@ impl FavoritesDB {
 
     pub fn get_all(&self) -> SqliteResult<Vec<Story>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, url, by, score, time, descendants
+            "SELECT id, title, url, by, score, time, descendants, kind
              FROM favorites
              ORDER BY time DESC",
         )?;
@@ -87,6 +198,7 @@ impl FavoritesDB {
         let stories = stmt.query_map([], |row| {
             Ok(Story {
                 id: row.get(0)?,
+                kind: ItemKind::from_name(&row.get::<_, String>(7)?),
                 title: row.get(1)?,
                 url: row.get(2)?,
                 by: row.get(3)?,
@@ -98,4 +210,501 @@ impl FavoritesDB {
 
         stories.collect()
     }
+
+    /// Every favorite with its save time, folder, tags and notes, newest first.
+    pub fn saved_favorites(&self) -> SqliteResult<Vec<SavedFavorite>> {
+        let mut tags = self.favorite_tags()?;
+        let mut stmt = self.conn.prepare(
+            "SELECT fav.id, fav.title, fav.url, fav.by, fav.score, fav.time, fav.descendants,
+                    fav.saved_at, f.name, fav.notes, fav.kind
+             FROM favorites fav LEFT JOIN folders f ON f.id = fav.folder_id
+             ORDER BY fav.time DESC",
+        )?;
+
+        let favorites = stmt.query_map([], |row| {
+            let id: u64 = row.get(0)?;
+            Ok(SavedFavorite {
+                story: Story {
+                    id,
+                    kind: ItemKind::from_name(&row.get::<_, String>(10)?),
+                    title: row.get(1)?,
+                    url: row.get(2)?,
+                    by: row.get(3)?,
+                    score: row.get(4)?,
+                    time: row.get(5)?,
+                    descendants: row.get(6)?,
+                },
+                saved_at: row.get::<_, i64>(7)? as u64,
+                folder: row.get(8)?,
+                tags: tags.remove(&id).unwrap_or_default(),
+                notes: row.get(9)?,
+            })
+        })?;
+
+        favorites.collect()
+    }
+
+    /// Saves imported favorites with their save time, folder, tags and notes
+    /// in one transaction. Ids that are already favorites are skipped.
+    /// Returns how many were added.
+    pub fn import_favorites(&self, favorites: &[SavedFavorite]) -> SqliteResult<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut added = 0;
+
+        for favorite in favorites {
+            let story = &favorite.story;
+            if let Some(ref folder) = favorite.folder {
+                tx.execute("INSERT OR IGNORE INTO folders (name) VALUES (?1)", [folder])?;
+            }
+
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO favorites
+                    (id, title, url, by, score, time, descendants, saved_at, notes, folder_id, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9,
+                         (SELECT id FROM folders WHERE name = ?10), ?11)",
+                (
+                    &(story.id as i64),
+                    &story.title,
+                    &story.url,
+                    &story.by,
+                    &story.score,
+                    &(story.time as i64),
+                    &story.descendants,
+                    &(favorite.saved_at as i64),
+                    &favorite.notes,
+                    &favorite.folder,
+                    story.kind.name(),
+                ),
+            )?;
+            if inserted == 0 {
+                continue;
+            }
+            added += 1;
+
+            for tag in &favorite.tags {
+                tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO favorite_tags (favorite_id, tag_id)
+                     SELECT ?1, id FROM tags WHERE name = ?2",
+                    (&(story.id as i64), tag),
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// Tags of every favorite, keyed by favorite id, sorted by name.
+    pub fn favorite_tags(&self) -> SqliteResult<HashMap<u64, Vec<String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT ft.favorite_id, t.name
+             FROM favorite_tags ft JOIN tags t ON t.id = ft.tag_id
+             ORDER BY t.name COLLATE NOCASE",
+        )?;
+
+        let mut tags: HashMap<u64, Vec<String>> = HashMap::new();
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, name) = row?;
+            tags.entry(id).or_default().push(name);
+        }
+
+        Ok(tags)
+    }
+
+    /// Every tag in use with the number of favorites carrying it.
+    pub fn tag_counts(&self) -> SqliteResult<Vec<(String, usize)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.name, COUNT(*)
+             FROM tags t JOIN favorite_tags ft ON ft.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY t.name COLLATE NOCASE",
+        )?;
+
+        let counts = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+        counts.collect()
+    }
+
+    pub fn add_tag(&self, favorite_id: u64, name: &str) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [name])?;
+        tx.execute(
+            "INSERT OR IGNORE INTO favorite_tags (favorite_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+            (&(favorite_id as i64), name),
+        )?;
+        tx.commit()
+    }
+
+    pub fn remove_tag(&self, favorite_id: u64, name: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "DELETE FROM favorite_tags
+             WHERE favorite_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+            (&(favorite_id as i64), name),
+        )?;
+        self.conn.execute(
+            "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM favorite_tags)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// All folders with the number of favorites in each, sorted by name.
+    pub fn folders(&self) -> SqliteResult<Vec<Folder>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT f.id, f.name, COUNT(fav.id)
+             FROM folders f LEFT JOIN favorites fav ON fav.folder_id = f.id
+             GROUP BY f.id
+             ORDER BY f.name COLLATE NOCASE",
+        )?;
+
+        let folders = stmt.query_map([], |row| {
+            Ok(Folder {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                count: row.get::<_, i64>(2)? as usize,
+            })
+        })?;
+
+        folders.collect()
+    }
+
+    /// The folder of every favorite that is in one, keyed by favorite id.
+    pub fn favorite_folders(&self) -> SqliteResult<HashMap<u64, i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, folder_id FROM favorites WHERE folder_id IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    pub fn create_folder(&self, name: &str) -> SqliteResult<()> {
+        self.conn.execute("INSERT OR IGNORE INTO folders (name) VALUES (?1)", [name])?;
+        Ok(())
+    }
+
+    /// Deletes a folder; its favorites become unfiled.
+    pub fn delete_folder(&self, folder_id: i64) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM folders WHERE id = ?1", [folder_id])?;
+        Ok(())
+    }
+
+    pub fn set_folder(&self, favorite_id: u64, folder_id: Option<i64>) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE favorites SET folder_id = ?1 WHERE id = ?2",
+            (folder_id, &(favorite_id as i64)),
+        )?;
+        Ok(())
+    }
+
+    /// Non-empty notes of every favorite, keyed by favorite id.
+    pub fn notes(&self) -> SqliteResult<HashMap<u64, String>> {
+        let mut stmt = self.conn.prepare("SELECT id, notes FROM favorites WHERE notes != ''")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    /// When each favorite was saved.
+    pub fn saved_times(&self) -> SqliteResult<HashMap<u64, u64>> {
+        let mut stmt = self.conn.prepare("SELECT id, saved_at FROM favorites")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64)))?;
+        rows.collect()
+    }
+
+    pub fn set_notes(&self, favorite_id: u64, notes: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE favorites SET notes = ?1 WHERE id = ?2",
+            (notes, &(favorite_id as i64)),
+        )?;
+        Ok(())
+    }
+
+    /// Searches favorites by title, url, author, notes and archived article
+    /// text, ranked by bm25 with title matches weighted highest. Each word
+    /// of `text` is matched as a prefix and all must be present.
+    pub fn search_favorites(&self, text: &str) -> SqliteResult<Vec<FavoriteMatch>> {
+        let Some(query) = fts_query(text) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT rowid,
+                    highlight(favorites_fts, 0, ?2, ?3),
+                    snippet(favorites_fts, -1, ?2, ?3, '…', 16)
+             FROM favorites_fts
+             WHERE favorites_fts MATCH ?1
+             ORDER BY bm25(favorites_fts, 10.0, 2.0, 4.0, 3.0, 1.0)",
+        )?;
+
+        let start = MATCH_START.to_string();
+        let end = MATCH_END.to_string();
+        let matches = stmt.query_map((&query, &start, &end), |row| {
+            let title: String = row.get(1)?;
+            let snippet: String = row.get(2)?;
+            let snippet = (snippet != title && snippet.contains(MATCH_START)).then_some(snippet);
+            Ok(FavoriteMatch {
+                id: row.get::<_, i64>(0)? as u64,
+                title,
+                snippet,
+            })
+        })?;
+
+        matches.collect()
+    }
+
+    /// Stores an archived article compressed, replacing any earlier copy, and
+    /// adds its text to the full-text index.
+    pub fn save_archive(&self, favorite_id: u64, article: &Article) -> SqliteResult<()> {
+        let json =
+            serde_json::to_vec(article).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let content = compress(&json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO archives (id, url, content, size, archived_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (
+                &(favorite_id as i64),
+                &article.url,
+                &content,
+                &(json.len() as i64),
+                &(unix_now() as i64),
+            ),
+        )?;
+        tx.execute(
+            "UPDATE favorites_fts SET article = ?1 WHERE rowid = ?2",
+            (&article.plain_text(), &(favorite_id as i64)),
+        )?;
+        tx.commit()
+    }
+
+    pub fn archive(&self, favorite_id: u64) -> SqliteResult<Option<Article>> {
+        let content: Option<Vec<u8>> = self
+            .conn
+            .query_row("SELECT content FROM archives WHERE id = ?1", [&(favorite_id as i64)], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let Some(content) = content else {
+            return Ok(None);
+        };
+
+        let article = decompress(&content)
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+            .and_then(|json| serde_json::from_slice(&json).map_err(Into::into))
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, e))?;
+        Ok(Some(article))
+    }
+
+    pub fn archived_ids(&self) -> SqliteResult<HashSet<u64>> {
+        let mut stmt = self.conn.prepare("SELECT id FROM archives")?;
+        let ids = stmt.query_map([], |row| Ok(row.get::<_, i64>(0)? as u64))?;
+        ids.collect()
+    }
+
+    pub fn cache_list(&self, category: Category, ids: &[u64]) -> SqliteResult<()> {
+        let ids_json =
+            serde_json::to_string(ids).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO cached_lists (category, ids, fetched_at) VALUES (?1, ?2, ?3)",
+            (category.api_endpoint(), &ids_json, &(unix_now() as i64)),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn cached_list(&self, category: Category) -> SqliteResult<Option<CachedList>> {
+        let row: Option<(String, i64)> = self
+            .conn
+            .query_row(
+                "SELECT ids, fetched_at FROM cached_lists WHERE category = ?1",
+                [category.api_endpoint()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        Ok(row.and_then(|(ids_json, fetched_at)| {
+            let ids = serde_json::from_str(&ids_json).ok()?;
+            Some(CachedList {
+                ids,
+                fetched_at: fetched_at as u64,
+            })
+        }))
+    }
+
+    pub fn cache_items(&self, items: &[Item]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let fetched_at = unix_now() as i64;
+
+        {
+            let mut stmt =
+                tx.prepare("INSERT OR REPLACE INTO cached_items (id, json, fetched_at) VALUES (?1, ?2, ?3)")?;
+            for item in items {
+                let json =
+                    serde_json::to_string(item).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+                stmt.execute((&(item.id as i64), &json, &fetched_at))?;
+            }
+        }
+
+        tx.commit()
+    }
+
+    /// Looks up cached items by id. Ids that are not cached are left out.
+    pub fn cached_items(&self, ids: &[u64]) -> SqliteResult<HashMap<u64, Item>> {
+        let mut stmt = self.conn.prepare_cached("SELECT json FROM cached_items WHERE id = ?1")?;
+        let mut items = HashMap::with_capacity(ids.len());
+
+        for &id in ids {
+            let json: Option<String> = stmt.query_row([&(id as i64)], |row| row.get(0)).optional()?;
+            if let Some(item) = json.and_then(|json| serde_json::from_str::<Item>(&json).ok()) {
+                items.insert(id, item);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Drops cached items that have not been refreshed for `max_age_secs`.
+    pub fn prune_cache(&self, max_age_secs: u64) -> SqliteResult<()> {
+        let cutoff = unix_now().saturating_sub(max_age_secs) as i64;
+        self.conn.execute("DELETE FROM cached_items WHERE fetched_at < ?1", [&cutoff])?;
+        Ok(())
+    }
+
+    /// Records the score, rank and comment count of freshly fetched stories.
+    /// `ranked` pairs each story with its 1-based rank in `category`.
+    pub fn record_history(&self, category: Category, ranked: &[(usize, &Story)]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let recorded_at = unix_now() as i64;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO story_history (story_id, category, rank, score, comments, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for (rank, story) in ranked {
+                stmt.execute((
+                    &(story.id as i64),
+                    category.api_endpoint(),
+                    &(*rank as i64),
+                    &story.score,
+                    &(story.descendants.unwrap_or(0) as i64),
+                    &recorded_at,
+                ))?;
+            }
+        }
+
+        tx.commit()
+    }
+
+    /// A story's recorded history across all categories, oldest first.
+    pub fn story_history(&self, id: u64) -> SqliteResult<Vec<HistoryPoint>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT category, rank, score, comments, recorded_at FROM story_history
+             WHERE story_id = ?1 ORDER BY recorded_at",
+        )?;
+
+        let rows = stmt.query_map([&(id as i64)], |row| {
+            let endpoint: String = row.get(0)?;
+            let (rank, score, comments, recorded_at): (i64, i32, i64, i64) =
+                (row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?);
+            Ok(Category::from_endpoint(&endpoint).map(|category| HistoryPoint {
+                category,
+                rank: rank as usize,
+                score,
+                comments: comments as u64,
+                recorded_at: recorded_at as u64,
+            }))
+        })?;
+
+        rows.filter_map(Result::transpose).collect()
+    }
+
+    /// Drops history recorded more than `max_age_secs` ago.
+    pub fn prune_history(&self, max_age_secs: u64) -> SqliteResult<()> {
+        let cutoff = unix_now().saturating_sub(max_age_secs) as i64;
+        self.conn.execute("DELETE FROM story_history WHERE recorded_at < ?1", [&cutoff])?;
+        Ok(())
+    }
+
+    pub fn mark_read(&self, ids: &[u64]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let read_at = unix_now() as i64;
+
+        {
+            let mut stmt = tx.prepare("INSERT OR IGNORE INTO read_items (id, read_at) VALUES (?1, ?2)")?;
+            for &id in ids {
+                stmt.execute((&(id as i64), &read_at))?;
+            }
+        }
+
+        tx.commit()
+    }
+
+    pub fn read_ids(&self) -> SqliteResult<HashSet<u64>> {
+        let mut stmt = self.conn.prepare("SELECT id FROM read_items")?;
+        let ids = stmt.query_map([], |row| row.get(0))?;
+        ids.collect()
+    }
+
+    pub fn record_visit(&self, id: u64, descendants: u64, kids: &[u64]) -> SqliteResult<Visit> {
+        let kids_json =
+            serde_json::to_string(kids).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let visited_at = unix_now();
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO story_visits (id, descendants, kids, visited_at) VALUES (?1, ?2, ?3, ?4)",
+            (&(id as i64), &(descendants as i64), &kids_json, &(visited_at as i64)),
+        )?;
+
+        Ok(Visit {
+            descendants,
+            kids: kids.iter().copied().collect(),
+            visited_at,
+        })
+    }
+
+    pub fn visits(&self) -> SqliteResult<HashMap<u64, Visit>> {
+        let mut stmt = self.conn.prepare("SELECT id, descendants, kids, visited_at FROM story_visits")?;
+
+        let visits = stmt.query_map([], |row| {
+            let kids_json: String = row.get(2)?;
+            let kids: Vec<u64> = serde_json::from_str(&kids_json).unwrap_or_default();
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                Visit {
+                    descendants: row.get::<_, i64>(1)? as u64,
+                    kids: kids.into_iter().collect(),
+                    visited_at: row.get::<_, i64>(3)? as u64,
+                },
+            ))
+        })?;
+
+        visits.collect()
+    }
+}
+
+/// Turns free text into an FTS5 query: every word quoted and prefix-matched,
+/// so user input never hits FTS5 syntax errors.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+fn decompress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut out)?;
+    Ok(out)
 }
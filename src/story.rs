@@ -1,19 +1,37 @@
-use serde::Deserialize;
+use crate::item::{Item, ItemKind};
 use time::OffsetDateTime;
 
-#[derive(Debug, Clone, Deserialize)]
+/// The flattened form of an item that story cards and `FavoritesDB` work with.
+/// Any `Item` converts into one, filling in placeholders for missing fields.
+#[derive(Debug, Clone)]
 pub struct Story {
     pub id: u64,
+    pub kind: ItemKind,
     pub title: String,
-    #[serde(default)]
     pub url: Option<String>,
     pub by: String,
     pub score: i32,
     pub time: u64,
-    #[serde(default)]
     pub descendants: Option<u64>,
 }
 
+impl From<Item> for Story {
+    fn from(item: Item) -> Self {
+        let title = item.display_title();
+
+        Self {
+            id: item.id,
+            kind: item.kind,
+            title,
+            url: item.url,
+            by: item.by.unwrap_or_else(|| "[deleted]".to_string()),
+            score: item.score.unwrap_or(0),
+            time: item.time,
+            descendants: item.descendants,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct StoryDisplay {
     pub story: Story,
@@ -32,24 +50,67 @@ impl StoryDisplay {
     }
 
     pub fn time_ago(&self) -> String {
-        let Ok(datetime) = OffsetDateTime::from_unix_timestamp(self.story.time as i64) else {
-            return "unknown time".to_string();
-        };
-        let now = OffsetDateTime::now_utc();
-        let duration = now - datetime;
-
-        if duration.whole_hours() > 24 {
-            format!("{} days ago", duration.whole_days())
-        } else if duration.whole_hours() > 0 {
-            format!("{} hours ago", duration.whole_hours())
-        } else if duration.whole_minutes() > 0 {
-            format!("{} minutes ago", duration.whole_minutes())
-        } else {
-            "just now".to_string()
-        }
+        time_ago(self.story.time)
     }
 
     pub fn hn_url(&self) -> String {
         format!("https://news.ycombinator.com/item?id={}", self.story.id)
     }
 }
+
+/// Lowercases a domain and drops a leading `www.` and anything pasted
+/// around the host, e.g. `https://www.Example.com/a` becomes `example.com`.
+pub fn normalize_domain(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    let host = name
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or("");
+    host.strip_prefix("www.").unwrap_or(host).to_string()
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+pub fn domain_matches(host: &str, domain: &str) -> bool {
+    let (host, domain) = (normalize_domain(host), normalize_domain(domain));
+    !domain.is_empty()
+        && (host == domain
+            || host
+                .strip_suffix(&domain)
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+/// Current time as a unix timestamp in seconds.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Formats a unix timestamp as a calendar date, e.g. "2024-03-01".
+pub fn date(unix_time: u64) -> String {
+    OffsetDateTime::from_unix_timestamp(unix_time as i64)
+        .map(|dt| dt.date().to_string())
+        .unwrap_or_default()
+}
+
+/// Formats a unix timestamp relative to now, e.g. "3 hours ago".
+pub fn time_ago(unix_time: u64) -> String {
+    let Ok(datetime) = OffsetDateTime::from_unix_timestamp(unix_time as i64) else {
+        return "unknown time".to_string();
+    };
+    let now = OffsetDateTime::now_utc();
+    let duration = now - datetime;
+
+    if duration.whole_hours() > 24 {
+        format!("{} days ago", duration.whole_days())
+    } else if duration.whole_hours() > 0 {
+        format!("{} hours ago", duration.whole_hours())
+    } else if duration.whole_minutes() > 0 {
+        format!("{} minutes ago", duration.whole_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
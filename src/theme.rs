@@ -1,3 +1,4 @@
+use crate::watch::WatchColor;
 use eframe::egui::Color32;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +16,58 @@ impl GruvboxTheme {
         }
     }
 
+    /// Colour used to draw attention to new content, such as unseen comments.
+    pub fn highlight_color(&self) -> Color32 {
+        match self {
+            GruvboxTheme::Dark => GruvboxDark::YELLOW,
+            GruvboxTheme::Light => GruvboxLight::YELLOW,
+        }
+    }
+
+    /// Colour of headings in the article reader.
+    pub fn heading_color(&self) -> Color32 {
+        match self {
+            GruvboxTheme::Dark => GruvboxDark::ORANGE,
+            GruvboxTheme::Light => GruvboxLight::ORANGE,
+        }
+    }
+
+    /// Colour of block quotes and captions in the article reader.
+    pub fn quote_color(&self) -> Color32 {
+        match self {
+            GruvboxTheme::Dark => GruvboxDark::AQUA,
+            GruvboxTheme::Light => GruvboxLight::AQUA,
+        }
+    }
+
+    /// Background of code blocks in the article reader.
+    pub fn code_background(&self) -> Color32 {
+        match self {
+            GruvboxTheme::Dark => GruvboxDark::BG1,
+            GruvboxTheme::Light => GruvboxLight::BG1,
+        }
+    }
+
+    /// Colour of a watch list badge.
+    pub fn watch_color(&self, color: WatchColor) -> Color32 {
+        match (self, color) {
+            (GruvboxTheme::Dark, WatchColor::Yellow) => GruvboxDark::YELLOW,
+            (GruvboxTheme::Dark, WatchColor::Green) => GruvboxDark::GREEN,
+            (GruvboxTheme::Dark, WatchColor::Aqua) => GruvboxDark::AQUA,
+            (GruvboxTheme::Dark, WatchColor::Blue) => GruvboxDark::BLUE,
+            (GruvboxTheme::Dark, WatchColor::Purple) => GruvboxDark::PURPLE,
+            (GruvboxTheme::Dark, WatchColor::Red) => GruvboxDark::RED,
+            (GruvboxTheme::Dark, WatchColor::Orange) => GruvboxDark::ORANGE,
+            (GruvboxTheme::Light, WatchColor::Yellow) => GruvboxLight::YELLOW,
+            (GruvboxTheme::Light, WatchColor::Green) => GruvboxLight::GREEN,
+            (GruvboxTheme::Light, WatchColor::Aqua) => GruvboxLight::AQUA,
+            (GruvboxTheme::Light, WatchColor::Blue) => GruvboxLight::BLUE,
+            (GruvboxTheme::Light, WatchColor::Purple) => GruvboxLight::PURPLE,
+            (GruvboxTheme::Light, WatchColor::Red) => GruvboxLight::RED,
+            (GruvboxTheme::Light, WatchColor::Orange) => GruvboxLight::ORANGE,
+        }
+    }
+
     fn colors(&self) -> ThemeColors {
         match self {
             GruvboxTheme::Dark => ThemeColors {
@@ -0,0 +1,147 @@
+use crate::hn_api::ApiError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+
+pub type TransportFuture<'a, T = String> =
+    Pin<Box<dyn Future<Output = Result<T, ApiError>> + Send + 'a>>;
+
+/// Performs the GET requests behind `HnClient`, returning the response body.
+/// Swapping the implementation lets the networking layer run without the live APIs.
+pub trait Transport: Send + Sync {
+    fn get<'a>(&'a self, url: &'a str, timeout: Duration) -> TransportFuture<'a>;
+
+    /// Like `get`, for binary bodies such as images. Bodies longer than
+    /// `max_bytes` fail with `ApiError::TooLarge`.
+    fn get_bytes<'a>(
+        &'a self,
+        url: &'a str,
+        timeout: Duration,
+        max_bytes: usize,
+    ) -> TransportFuture<'a, Vec<u8>>;
+}
+
+/// Real HTTP transport backed by reqwest.
+pub struct HttpTransport {
+    client: reqwest::Client,
+}
+
+impl HttpTransport {
+    pub fn new(user_agent: &str) -> Result<Self, ApiError> {
+        let client = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self { client })
+    }
+
+    async fn send(&self, url: &str, timeout: Duration) -> Result<reqwest::Response, ApiError> {
+        let response = self.client.get(url).timeout(timeout).send().await?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok());
+            return Err(ApiError::RateLimited { retry_after });
+        }
+
+        Ok(response.error_for_status()?)
+    }
+}
+
+impl Transport for HttpTransport {
+    fn get<'a>(&'a self, url: &'a str, timeout: Duration) -> TransportFuture<'a> {
+        Box::pin(async move { Ok(self.send(url, timeout).await?.text().await?) })
+    }
+
+    fn get_bytes<'a>(
+        &'a self,
+        url: &'a str,
+        timeout: Duration,
+        max_bytes: usize,
+    ) -> TransportFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let mut response = self.send(url, timeout).await?;
+            let too_large = ApiError::TooLarge { limit: max_bytes };
+            if response
+                .content_length()
+                .is_some_and(|length| length > max_bytes as u64)
+            {
+                return Err(too_large);
+            }
+
+            // The length header may be missing or wrong, so count as we read.
+            let mut body = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > max_bytes {
+                    return Err(too_large);
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(body)
+        })
+    }
+}
+
+/// Serves canned response bodies keyed by full request URL.
+#[derive(Default)]
+pub struct FixtureTransport {
+    responses: HashMap<String, String>,
+}
+
+impl FixtureTransport {
+    /// Loads fixtures from a JSON object mapping request URLs to response bodies.
+    /// Body values that are not strings are stored as their JSON text.
+    pub fn from_file(path: &Path) -> Result<Self, ApiError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ApiError::Request(format!("{}: {}", path.display(), e)))?;
+        let entries: HashMap<String, serde_json::Value> = serde_json::from_str(&content)?;
+
+        let mut transport = Self::default();
+        for (url, body) in entries {
+            let body = match body {
+                serde_json::Value::String(text) => text,
+                other => other.to_string(),
+            };
+            transport.insert(url, body);
+        }
+
+        Ok(transport)
+    }
+
+    pub fn insert(&mut self, url: impl Into<String>, body: impl Into<String>) {
+        self.responses.insert(url.into(), body.into());
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn get<'a>(&'a self, url: &'a str, _timeout: Duration) -> TransportFuture<'a> {
+        // A missing fixture behaves like a 404 so it is not retried.
+        Box::pin(async move {
+            self.responses
+                .get(url)
+                .cloned()
+                .ok_or(ApiError::Status(404))
+        })
+    }
+
+    fn get_bytes<'a>(
+        &'a self,
+        url: &'a str,
+        timeout: Duration,
+        max_bytes: usize,
+    ) -> TransportFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let body = self.get(url, timeout).await?;
+            if body.len() > max_bytes {
+                return Err(ApiError::TooLarge { limit: max_bytes });
+            }
+            Ok(body.into_bytes())
+        })
+    }
+}
@@ -1,5 +1,175 @@
 use eframe::egui;
-use crate::{app::{HackerNewsApp, ViewMode}, story::StoryDisplay};
+use crate::{
+    action::Action,
+    app::{ArticleView, CommentView, HackerNewsApp, ImageState, ImportStage, ProfileTab, ProfileView, ViewMode},
+    article::ArticleBlock,
+    category::Category,
+    comment::CommentNode,
+    export::ExportFormat,
+    hn_api::ApiError,
+    html::Block,
+    import::EntryStatus,
+    item::ItemKind,
+    listing::{ListOptions, MaxAge, PostKind, StorySort},
+    mute::{self, MuteKind, MuteRule},
+    palette::Command,
+    search::{DateRange, SearchSort, SearchTag},
+    storage::{HistoryPoint, Visit, MATCH_END, MATCH_START},
+    story::{date, time_ago, unix_now, StoryDisplay},
+    theme::GruvboxTheme,
+    watch::WatchKind,
+};
+use std::collections::{HashMap, HashSet};
+
+const COMMENT_INDENT: f32 = 16.0;
+const MAX_COMMENT_INDENT_DEPTH: usize = 10;
+
+pub fn render_menu_bar(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+        egui::MenuBar::new().ui(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                if ui.button("📥 Import favorites…").clicked() {
+                    app.perform(Action::Import);
+                    ui.close();
+                }
+                ui.menu_button("📤 Export favorites", |ui| {
+                    for format in ExportFormat::ALL {
+                        action_button(ui, app, format.display_name(), Action::Export(format));
+                    }
+                });
+            });
+
+            action_button(ui, app, "🔎 Commands", Action::CommandPalette);
+            action_button(ui, app, "⚙ Settings", Action::ToggleSettings);
+
+            render_export_status(ui, app);
+        });
+    });
+}
+
+fn render_export_status(ui: &mut egui::Ui, app: &mut HackerNewsApp) {
+    let Some(status) = &app.export_status else {
+        return;
+    };
+
+    ui.separator();
+    match status {
+        Ok(path) => {
+            ui.label(format!("✔ Exported to {}", path.display()));
+            if ui.small_button("📂 Open folder").clicked() {
+                if let Some(dir) = path.parent() {
+                    opener::open(dir).ok();
+                }
+            }
+        }
+        Err(e) => {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⚠ Export failed: {}", e));
+        }
+    }
+    if ui.small_button("✖").clicked() {
+        app.export_status = None;
+    }
+}
+
+pub fn render_import_dialog(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    let Some(dialog) = app.import_dialog.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let (mut load, mut commit, mut close) = (false, false, false);
+
+    egui::Window::new("📥 Import favorites")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(500.0)
+        .show(ctx, |ui| match &mut dialog.stage {
+            ImportStage::Choosing | ImportStage::Failed(_) => {
+                if let ImportStage::Failed(error) = &dialog.stage {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {}", error));
+                    ui.add_space(5.0);
+                }
+                ui.label("A JSON or CSV export, a browser bookmarks HTML file, or a saved copy of your HN favorites page:");
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut dialog.path)
+                            .hint_text("/path/to/file")
+                            .desired_width(380.0),
+                    );
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if submitted || ui.button("Load").clicked() {
+                        load = true;
+                    }
+                });
+            }
+            ImportStage::Resolving => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Looking up items on Hacker News...");
+                });
+            }
+            ImportStage::Preview(preview) => {
+                ui.label(egui::RichText::new(preview.source.display_name()).strong());
+                ui.label(format!(
+                    "{} new · {} already saved · {} not found",
+                    preview.count(|s| matches!(s, EntryStatus::New)),
+                    preview.count(|s| matches!(s, EntryStatus::AlreadySaved)),
+                    preview.count(|s| matches!(s, EntryStatus::Unresolved(_))),
+                ));
+                ui.add_space(5.0);
+
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for entry in &mut preview.entries {
+                        ui.horizontal(|ui| {
+                            let importable = matches!(entry.status, EntryStatus::New);
+                            ui.add_enabled(importable, egui::Checkbox::without_text(&mut entry.selected));
+                            ui.label(&entry.favorite.story.title);
+                            match &entry.status {
+                                EntryStatus::New => {}
+                                EntryStatus::AlreadySaved => {
+                                    ui.label(egui::RichText::new("already saved").weak());
+                                }
+                                EntryStatus::Unresolved(error) => {
+                                    ui.colored_label(ui.visuals().warn_fg_color, "not found")
+                                        .on_hover_text(error.to_string());
+                                }
+                            }
+                        });
+                    }
+                });
+
+                ui.separator();
+                let selected = preview.selected().len();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(selected > 0, egui::Button::new(format!("Import {}", selected)))
+                        .clicked()
+                    {
+                        commit = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            }
+            ImportStage::Done(added) => {
+                ui.label(format!("✔ Imported {} favorites", added));
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            }
+        });
+
+    if load {
+        app.load_import();
+    }
+    if commit {
+        app.commit_import();
+    }
+    if close || !open {
+        app.close_import_dialog();
+    }
+}
 
 pub fn render_sidebar(ctx: &egui::Context, app: &mut HackerNewsApp) {
     egui::SidePanel::left("sidebar")
@@ -9,16 +179,14 @@ pub fn render_sidebar(ctx: &egui::Context, app: &mut HackerNewsApp) {
             ui.add_space(5.0);
 
             // View mode toggle
-            ui.horizontal(|ui| {
-                let fetched_selected = app.view_mode == ViewMode::Fetched;
-                let saved_selected = app.view_mode == ViewMode::Saved;
-
-                if ui.selectable_label(fetched_selected, "Fetched").clicked() {
-                    app.set_view_mode(ViewMode::Fetched);
-                }
-
-                if ui.selectable_label(saved_selected, "Saved").clicked() {
-                    app.set_view_mode(ViewMode::Saved);
+            ui.horizontal_wrapped(|ui| {
+                for (mode, label) in [
+                    (ViewMode::Fetched, "Fetched"),
+                    (ViewMode::Saved, "Saved"),
+                    (ViewMode::Search, "Search"),
+                    (ViewMode::Watching, "Watching"),
+                ] {
+                    action_label(ui, app, app.view_mode == mode, label, Action::ShowView(mode));
                 }
             });
 
@@ -26,14 +194,29 @@ pub fn render_sidebar(ctx: &egui::Context, app: &mut HackerNewsApp) {
             ui.separator();
             ui.add_space(10.0);
 
+            render_search_box(ui, app);
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+
             // Theme toggle
             ui.heading("Theme");
             ui.add_space(5.0);
 
-            if ui.button(app.theme.display_name()).clicked() {
-                app.toggle_theme();
+            action_button(ui, app, app.theme.display_name(), Action::ToggleTheme);
+
+            let mut open_in_reader = app.open_in_reader();
+            if ui
+                .checkbox(&mut open_in_reader, "📖 Open stories in reader")
+                .on_hover_text("Off: story titles open in the browser")
+                .changed()
+            {
+                app.set_open_in_reader(open_in_reader);
             }
 
+            action_button(ui, app, "⌨ Key bindings", Action::ToggleHelp);
+
             ui.add_space(10.0);
             ui.separator();
             ui.add_space(10.0);
@@ -43,110 +226,571 @@ pub fn render_sidebar(ctx: &egui::Context, app: &mut HackerNewsApp) {
                 ui.heading("Categories");
                 ui.add_space(10.0);
 
-                for category in crate::category::Category::ALL {
+                for category in Category::ALL {
                     let is_selected = app.current_category == category;
-
-                    if ui.selectable_label(is_selected, category.display_name()).clicked() {
-                        app.current_category = category;
-                        app.fetch_current_category();
-                    }
+                    action_label(ui, app, is_selected, category.display_name(), Action::ShowCategory(category));
                 }
 
                 ui.add_space(20.0);
 
-                if ui.button("🔄 Refresh").clicked() {
-                    app.fetch_current_category();
+                action_button(ui, app, "🔄 Refresh", Action::Refresh);
+
+                ui.add_space(10.0);
+
+                let mut hide_read = app.hide_read();
+                let response = ui.checkbox(&mut hide_read, "Hide read");
+                if response.on_hover_text(action_hint(ctx, app, Action::ToggleHideRead)).changed() {
+                    app.perform(Action::ToggleHideRead);
                 }
-            } else {
+
+                action_button(ui, app, "✔ Mark all as read", Action::MarkAllRead);
+            } else if app.view_mode == ViewMode::Saved {
                 ui.heading("Saved Favorites");
                 ui.add_space(10.0);
                 ui.label(format!("{} stories", app.saved_stories.len()));
+
+                ui.add_space(10.0);
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut app.saved_filter)
+                        .hint_text("🔍 Search saved stories"),
+                );
+                if app.focus_search {
+                    response.request_focus();
+                    app.focus_search = false;
+                }
+                if response.changed() {
+                    app.search_saved();
+                }
+
+                let mut archive_articles = app.archive_articles();
+                if ui
+                    .checkbox(&mut archive_articles, "📦 Archive articles when saving")
+                    .changed()
+                {
+                    app.set_archive_articles(archive_articles);
+                }
+
+                ui.add_space(10.0);
+                render_saved_filters(ui, app);
+            } else if app.view_mode == ViewMode::Watching {
+                ui.heading("Watching");
+                ui.add_space(10.0);
+
+                action_button(ui, app, "🔄 Refresh all categories", Action::Refresh);
+
+                let mut hide_read = app.hide_read();
+                let response = ui.checkbox(&mut hide_read, "Hide read");
+                if response.on_hover_text(action_hint(ctx, app, Action::ToggleHideRead)).changed() {
+                    app.perform(Action::ToggleHideRead);
+                }
+
+                ui.add_space(10.0);
+                render_watch_rules(ui, app, WatchKind::User);
+                ui.add_space(10.0);
+                render_watch_rules(ui, app, WatchKind::Domain);
+            }
+        });
+}
+
+/// The watched authors or domains, each with its badge colour, plus a field
+/// to add another.
+fn render_watch_rules(ui: &mut egui::Ui, app: &mut HackerNewsApp, kind: WatchKind) {
+    let (heading, hint) = match kind {
+        WatchKind::User => ("👤 Authors", "Add author"),
+        WatchKind::Domain => ("🌐 Domains", "Add domain"),
+    };
+    ui.label(egui::RichText::new(heading).strong());
+
+    let mut cycle = None;
+    let mut remove = None;
+    for (index, rule) in app.watch_list().rules(kind).iter().enumerate() {
+        ui.horizontal(|ui| {
+            let swatch = egui::RichText::new("⬤").color(app.theme.watch_color(rule.color));
+            if ui.small_button(swatch).on_hover_text("Change badge colour").clicked() {
+                cycle = Some(index);
+            }
+            ui.label(&rule.name);
+            if ui.small_button("✖").on_hover_text("Stop watching").clicked() {
+                remove = Some(rule.name.clone());
+            }
+        });
+    }
+    if let Some(index) = cycle {
+        app.cycle_watch_color(kind, index);
+    }
+    if let Some(name) = remove {
+        app.unwatch(kind, &name);
+    }
+
+    ui.horizontal(|ui| {
+        let input = match kind {
+            WatchKind::User => &mut app.new_watch_user,
+            WatchKind::Domain => &mut app.new_watch_domain,
+        };
+        let response = ui.add(
+            egui::TextEdit::singleline(input)
+                .hint_text(hint)
+                .desired_width(120.0),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if submitted || ui.small_button("➕").clicked() {
+            let name = std::mem::take(input);
+            app.watch(kind, &name);
+        }
+    });
+}
+
+fn render_saved_filters(ui: &mut egui::Ui, app: &mut HackerNewsApp) {
+    ui.label(egui::RichText::new("📁 Folders").strong());
+
+    if ui.selectable_label(app.folder_filter.is_none(), "All").clicked() {
+        app.folder_filter = None;
+    }
+
+    let mut delete_folder = None;
+    for folder in &app.folders {
+        ui.horizontal(|ui| {
+            let selected = app.folder_filter == Some(folder.id);
+            let label = format!("{} ({})", folder.name, folder.count);
+            if ui.selectable_label(selected, label).clicked() {
+                app.folder_filter = if selected { None } else { Some(folder.id) };
+            }
+            if ui.small_button("✖").on_hover_text("Delete folder").clicked() {
+                delete_folder = Some(folder.id);
             }
         });
+    }
+    if let Some(folder_id) = delete_folder {
+        app.delete_folder(folder_id);
+    }
+
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app.new_folder_name)
+                .hint_text("New folder")
+                .desired_width(120.0),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if submitted || ui.small_button("➕").clicked() {
+            app.create_folder();
+        }
+    });
+
+    ui.add_space(10.0);
+    ui.label(egui::RichText::new("🏷 Tags").strong());
+
+    if app.tag_counts.is_empty() {
+        ui.label(egui::RichText::new("No tags yet").weak());
+        return;
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        for (tag, count) in &app.tag_counts {
+            let selected = app.tag_filter.as_ref() == Some(tag);
+            if ui.selectable_label(selected, format!("{} ({})", tag, count)).clicked() {
+                app.tag_filter = if selected { None } else { Some(tag.clone()) };
+            }
+        }
+    });
+}
+
+fn render_search_box(ui: &mut egui::Ui, app: &mut HackerNewsApp) {
+    ui.heading("Search");
+    ui.add_space(5.0);
+
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut app.search_query.text).hint_text("Search Hacker News"),
+    );
+    if app.focus_search && app.view_mode != ViewMode::Saved {
+        response.request_focus();
+        app.focus_search = false;
+    }
+    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+    egui::CollapsingHeader::new("Filters")
+        .id_salt("search_filters")
+        .show(ui, |ui| {
+            let query = &mut app.search_query;
+
+            egui::ComboBox::from_label("Type")
+                .selected_text(query.tag.map_or("Any", |t| t.display_name()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut query.tag, None, "Any");
+                    for tag in SearchTag::ALL {
+                        ui.selectable_value(&mut query.tag, Some(tag), tag.display_name());
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                ui.label("Author");
+                ui.text_edit_singleline(&mut query.author);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Min points");
+                ui.add(egui::DragValue::new(&mut query.min_points));
+            });
+
+            egui::ComboBox::from_label("Date")
+                .selected_text(query.date_range.display_name())
+                .show_ui(ui, |ui| {
+                    for range in DateRange::ALL {
+                        ui.selectable_value(&mut query.date_range, range, range.display_name());
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut query.sort, SearchSort::Relevance, "Relevance");
+                ui.radio_value(&mut query.sort, SearchSort::Date, "Date");
+            });
+        });
+
+    if (submitted || ui.button("🔍 Search").clicked()) && !app.is_searching {
+        app.run_search();
+    }
 }
 
 pub fn render_story_list(ctx: &egui::Context, app: &mut HackerNewsApp) {
     egui::CentralPanel::default().show(ctx, |ui| {
-        let (title, show_refresh) = match app.view_mode {
-            ViewMode::Fetched => (format!("{} Stories", app.current_category.display_name()), true),
-            ViewMode::Saved => (format!("Saved Favorites ({})", app.saved_stories.len()), false),
+        let title = match app.view_mode {
+            ViewMode::Fetched => format!("{} Stories", app.current_category.display_name()),
+            ViewMode::Saved => format!("Saved Favorites ({})", app.saved_stories.len()),
+            ViewMode::Search => format!("Search Results ({})", app.search_results.len()),
+            ViewMode::Watching => format!("Watching ({})", app.watched_stories.len()),
         };
 
         ui.heading(title);
 
-        if let Some(ref error) = app.error_message {
+        let (error, is_loading) = match app.view_mode {
+            ViewMode::Fetched => (app.fetch_error.clone(), app.is_loading),
+            ViewMode::Saved => (None, false),
+            ViewMode::Search => (app.search_error.clone(), app.is_searching),
+            // Stories from categories that did load are still worth showing.
+            ViewMode::Watching => (None, false),
+        };
+
+        // A cached snapshot stays on screen while it is revalidated, and
+        // replaces the error banner when the network is unavailable.
+        let showing_snapshot =
+            app.view_mode == ViewMode::Fetched && app.cached_at.is_some() && !app.stories.is_empty();
+
+        if let Some(error) = error.filter(|_| !showing_snapshot) {
             ui.add_space(10.0);
-            ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
-            if show_refresh && ui.button("🔄 Retry").clicked() {
-                app.fetch_current_category();
+            let context = match app.view_mode {
+                ViewMode::Search => "Search failed",
+                _ => "Failed to fetch stories",
+            };
+            render_api_error(ui, context, &error);
+            match app.view_mode {
+                ViewMode::Fetched => {
+                    if ui.button("🔄 Retry").clicked() {
+                        app.fetch_current_category();
+                    }
+                }
+                ViewMode::Search => {
+                    if ui.button("🔄 Retry").clicked() {
+                        app.run_search();
+                    }
+                }
+                ViewMode::Saved | ViewMode::Watching => {}
             }
             return;
         }
 
-        if app.is_loading {
+        if let (ViewMode::Fetched, Some(warning)) = (app.view_mode, &app.warning_message) {
+            ui.add_space(5.0);
+            ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", warning));
+        }
+
+        if app.view_mode == ViewMode::Watching {
+            if let Some(error) = &app.watch_error {
+                ui.add_space(5.0);
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("⚠ Some categories failed to load: {}", error),
+                );
+            }
+            if app.watch_loading > 0 {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Fetching {} categories...", app.watch_loading));
+                });
+            }
+        }
+
+        if showing_snapshot {
+            render_cache_status(ui, app);
+        }
+
+        if is_loading && !showing_snapshot {
             ui.add_space(10.0);
             ui.spinner();
             ui.label("Loading...");
             return;
         }
 
+        render_list_options(ui, app);
+
         // Collect story data to avoid borrow conflicts
-        let stories: Vec<(StoryDisplay, bool)> = match app.view_mode {
-            ViewMode::Fetched => {
-                app.stories.iter()
-                    .map(|s| (s.clone(), app.is_favorite(s.story.id)))
-                    .collect()
-            }
-            ViewMode::Saved => {
-                app.saved_stories.iter()
-                    .map(|s| (s.clone(), true))
-                    .collect()
-            }
-        };
+        let hide_read = app.hide_read();
+        let (stories, muted) = app.list_stories();
+
+        if muted > 0 {
+            let label = format!("🔇 {} hidden", muted);
+            action_label(ui, app, app.show_muted, &label, Action::ToggleMuted);
+        }
 
         if stories.is_empty() {
             ui.add_space(10.0);
             let msg = match app.view_mode {
+                _ if muted > 0 => "Every story here is muted. Click \"hidden\" above to reveal them.",
+                _ if app.list_options().has_filters() => "No stories match the current filters.",
+                ViewMode::Fetched if hide_read && !app.stories.is_empty() => {
+                    "All loaded stories have been read. Untick \"Hide read\" to show them."
+                }
                 ViewMode::Fetched => "No stories available.",
                 ViewMode::Saved => "No saved favorites yet. Click the ⭐ button to save stories.",
+                ViewMode::Search => "No results. Enter a query in the sidebar and press Enter.",
+                ViewMode::Watching if app.watch_list().is_empty() => {
+                    "Nothing is watched yet. Add authors or domains in the sidebar."
+                }
+                ViewMode::Watching => {
+                    "No watched stories among the loaded ones. Refresh to fetch every category."
+                }
             };
             ui.label(msg);
+
+            if app.view_mode == ViewMode::Fetched {
+                ui.add_space(10.0);
+                render_load_more(ui, app);
+            }
             return;
         }
 
         egui::ScrollArea::vertical().show(ui, |ui| {
             for (story_display, is_favorite) in stories {
-                render_story_card(ui, app, &story_display, is_favorite);
+                let selected = app.selected_story == Some(story_display.story.id);
+                let stroke = if selected {
+                    egui::Stroke::new(1.5, app.theme.highlight_color())
+                } else {
+                    egui::Stroke::NONE
+                };
+                let response = egui::Frame::new()
+                    .stroke(stroke)
+                    .inner_margin(4.0)
+                    .corner_radius(4.0)
+                    .show(ui, |ui| render_story_card(ui, app, &story_display, is_favorite))
+                    .response;
+                if selected && app.scroll_to_selected {
+                    response.scroll_to_me(Some(egui::Align::Center));
+                    app.scroll_to_selected = false;
+                }
                 ui.add_space(10.0);
                 ui.separator();
                 ui.add_space(10.0);
             }
+
+            if app.view_mode == ViewMode::Fetched {
+                render_load_more(ui, app);
+            }
         });
     });
 }
 
+/// Sort order and filter controls above the story list.
+fn render_list_options(ui: &mut egui::Ui, app: &mut HackerNewsApp) {
+    let mut options = app.list_options().clone();
+    let mut editing = false;
+
+    ui.horizontal_wrapped(|ui| {
+        egui::ComboBox::from_id_salt("list_sort")
+            .selected_text(format!("Sort: {}", options.sort.display_name()))
+            .show_ui(ui, |ui| {
+                for sort in StorySort::ALL {
+                    ui.selectable_value(&mut options.sort, sort, sort.display_name());
+                }
+            });
+        ui.toggle_value(&mut options.reverse, "⇅").on_hover_text("Reverse order");
+
+        ui.separator();
+
+        ui.label("Min points");
+        let min_score = ui.add(egui::DragValue::new(&mut options.min_score).range(0..=5000).speed(5));
+        ui.label("Min comments");
+        let min_comments =
+            ui.add(egui::DragValue::new(&mut options.min_comments).range(0..=5000).speed(5));
+        // Hold off saving until the drag is released or the typed value is committed.
+        editing = [min_score, min_comments]
+            .iter()
+            .any(|value| value.dragged() || value.has_focus());
+
+        egui::ComboBox::from_id_salt("list_max_age")
+            .selected_text(options.max_age.display_name())
+            .show_ui(ui, |ui| {
+                for max_age in MaxAge::ALL {
+                    ui.selectable_value(&mut options.max_age, max_age, max_age.display_name());
+                }
+            });
+        egui::ComboBox::from_id_salt("list_kind")
+            .selected_text(options.kind.display_name())
+            .show_ui(ui, |ui| {
+                for kind in PostKind::ALL {
+                    ui.selectable_value(&mut options.kind, kind, kind.display_name());
+                }
+            });
+
+        if options != ListOptions::default() && ui.button("Reset").clicked() {
+            options = ListOptions::default();
+        }
+    });
+
+    if &options != app.list_options() {
+        app.set_list_options(options);
+    }
+    if !editing {
+        app.save_list_options();
+    }
+}
+
+fn render_api_error(ui: &mut egui::Ui, context: &str, error: &ApiError) {
+    ui.colored_label(egui::Color32::RED, format!("❌ {}: {}", context, error));
+    ui.label(egui::RichText::new(error.hint()).weak());
+}
+
+fn render_cache_status(ui: &mut egui::Ui, app: &mut HackerNewsApp) {
+    let Some(cached_at) = app.cached_at else {
+        return;
+    };
+
+    ui.add_space(5.0);
+    ui.horizontal(|ui| {
+        if let Some(error) = app.fetch_error.clone() {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("📴 Offline — showing cached snapshot from {}", time_ago(cached_at)),
+            )
+            .on_hover_text(format!("{}\n{}", error, error.hint()));
+
+            if ui.button("🔄 Retry").clicked() {
+                app.fetch_current_category();
+            }
+        } else {
+            ui.label(egui::RichText::new(format!("🗄 cached {}", time_ago(cached_at))).weak());
+
+            if app.is_loading {
+                ui.spinner();
+                ui.label("Refreshing...");
+            }
+        }
+    });
+}
+
+fn render_load_more(ui: &mut egui::Ui, app: &mut HackerNewsApp) {
+    if app.is_loading_more {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Loading more...");
+        });
+        return;
+    }
+
+    let remaining = app.remaining_count();
+    if remaining > 0 && ui.button(format!("⬇ Load more ({} remaining)", remaining)).clicked() {
+        app.load_more();
+    }
+}
+
 fn render_story_card(ui: &mut egui::Ui, app: &mut HackerNewsApp, story_display: &StoryDisplay, is_favorite: bool) {
+    ui.scope(|ui| {
+        if app.is_read(story_display.story.id) {
+            ui.multiply_opacity(0.5);
+        }
+        // Only shown while muted stories are revealed.
+        if let Some(rule) = app.muted_by(story_display).filter(|_| !is_favorite) {
+            ui.multiply_opacity(0.5);
+            ui.label(egui::RichText::new(format!("🔇 Muted by {}", rule.description())).weak());
+        }
+        render_story_card_contents(ui, app, story_display, is_favorite);
+    });
+}
+
+fn render_story_card_contents(
+    ui: &mut egui::Ui,
+    app: &mut HackerNewsApp,
+    story_display: &StoryDisplay,
+    is_favorite: bool,
+) {
     let story = &story_display.story;
 
     ui.horizontal(|ui| {
-        ui.label(format!("⬤ {} points by {} | {}", story.score, story.by, story_display.time_ago()));
+        if story.kind != ItemKind::Story {
+            ui.label(egui::RichText::new(format!("[{}]", story.kind.display_name())).strong());
+        }
+        ui.label(format!("⬤ {} points by", story.score));
+        if ui.link(&story.by).on_hover_text("View profile").clicked() {
+            app.open_profile(&story.by);
+        }
+        ui.label(format!("| {}", story_display.time_ago()));
+
+        for watch in app.watch_list().matches(story_display) {
+            let color = app.theme.watch_color(watch.color);
+            let badge = egui::RichText::new(format!("👁 {}", watch.name)).color(color).strong();
+            ui.label(badge).on_hover_text(watch.hover_text());
+        }
+
+        let history = app.history(story.id).to_vec();
+        if history.len() >= 2 {
+            let (first, last) = (&history[0], &history[history.len() - 1]);
+            let hover = format!(
+                "Score {} → {} since {}\nClick for rank and comment history",
+                first.score,
+                last.score,
+                time_ago(first.recorded_at)
+            );
+            if sparkline(ui, &history, app.theme.highlight_color()).on_hover_text(hover).clicked() {
+                app.open_history(story_display);
+            }
+        }
     });
 
     ui.add_space(5.0);
 
     let hn_url = story_display.hn_url();
     let url_to_open = story.url.as_ref().unwrap_or(&hn_url);
-    ui.hyperlink_to(format!("🔗 {}", story.title), url_to_open);
+    let search_match = match app.view_mode {
+        ViewMode::Saved => app.saved_match(story.id).cloned(),
+        _ => None,
+    };
+    let title: egui::WidgetText = match &search_match {
+        Some(m) => highlighted_text(ui, &format!("🔗 {}", m.title), ui.visuals().hyperlink_color, app.theme).into(),
+        None => format!("🔗 {}", story.title).into(),
+    };
+    if app.open_in_reader() {
+        if ui.link(title).on_hover_text(url_to_open).clicked() {
+            app.mark_read(story.id);
+            app.open_reader(story_display);
+        }
+    } else if ui.hyperlink_to(title, url_to_open).clicked() {
+        app.mark_read(story.id);
+    }
 
     if let Some(ref domain) = story_display.domain {
         ui.label(format!("({})", domain));
     }
 
+    if let Some(snippet) = search_match.and_then(|m| m.snippet) {
+        ui.label(highlighted_text(ui, &snippet, ui.visuals().weak_text_color(), app.theme));
+    }
+
     ui.add_space(5.0);
 
     ui.horizontal(|ui| {
         ui.label(format!("💬 {} comments", story.descendants.unwrap_or(0)));
 
+        if let Some(new_comments) = app.new_comment_count(story).filter(|&n| n > 0) {
+            ui.colored_label(app.theme.highlight_color(), format!("+{} new", new_comments));
+        }
+
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             // Star button
             let star_label = if is_favorite { "⭐" } else { "☆" };
@@ -155,16 +799,1082 @@ fn render_story_card(ui: &mut egui::Ui, app: &mut HackerNewsApp, story_display:
             }
 
             if ui.button("💬 Discuss").clicked() {
-                opener::open(story_display.hn_url().as_str()).ok();
+                app.mark_read(story.id);
+                app.open_comments(story_display);
             }
 
             if let Some(ref url) = story.url {
                 if ui.button("🔗 Story").clicked() {
+                    app.mark_read(story.id);
                     opener::open(url.as_str()).ok();
                 }
             } else if ui.button("🔗 Story").clicked() {
+                app.mark_read(story.id);
                 opener::open(story_display.hn_url().as_str()).ok();
             }
         });
     });
+
+    if is_favorite {
+        ui.add_space(5.0);
+        render_favorite_organiser(ui, app, story.id);
+        render_archive_controls(ui, app, story_display);
+    }
+}
+
+fn render_archive_controls(ui: &mut egui::Ui, app: &mut HackerNewsApp, story_display: &StoryDisplay) {
+    let story = &story_display.story;
+    if story.url.is_none() {
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        if app.archiving.contains(&story.id) {
+            ui.spinner();
+            ui.label("Archiving...");
+            return;
+        }
+
+        if app.is_archived(story.id) {
+            if ui.button("📖 Read offline").clicked() {
+                app.mark_read(story.id);
+                app.open_reader(story_display);
+            }
+            if ui.small_button("🔄").on_hover_text("Archive again").clicked() {
+                app.archive_story(story);
+            }
+        } else if ui.button("📦 Archive").clicked() {
+            app.archive_story(story);
+        }
+
+        if let Some(error) = app.archive_errors.get(&story.id) {
+            ui.colored_label(ui.visuals().warn_fg_color, "⚠ Archiving failed")
+                .on_hover_text(format!("{}\n{}", error, error.hint()));
+        }
+    });
+}
+
+/// Lays out text containing `MATCH_START`/`MATCH_END` markers, drawing the
+/// marked ranges in the theme's highlight colour.
+fn highlighted_text(ui: &egui::Ui, text: &str, color: egui::Color32, theme: GruvboxTheme) -> egui::text::LayoutJob {
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let plain = egui::TextFormat::simple(font_id.clone(), color);
+    let highlight = egui::TextFormat {
+        font_id,
+        color: theme.highlight_color(),
+        underline: egui::Stroke::new(1.0, theme.highlight_color()),
+        ..Default::default()
+    };
+
+    let mut job = egui::text::LayoutJob::default();
+    for (i, part) in text.split(MATCH_START).enumerate() {
+        match part.split_once(MATCH_END) {
+            Some((matched, rest)) if i > 0 => {
+                job.append(matched, 0.0, highlight.clone());
+                job.append(rest, 0.0, plain.clone());
+            }
+            _ => job.append(part, 0.0, plain.clone()),
+        }
+    }
+    job
+}
+
+/// Tag chips, a field to add tags and a folder picker for a saved story.
+fn render_favorite_organiser(ui: &mut egui::Ui, app: &mut HackerNewsApp, id: u64) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("🏷");
+
+        let mut removed = None;
+        for tag in app.tags_of(id) {
+            ui.group(|ui| {
+                ui.spacing_mut().item_spacing.x = 2.0;
+                ui.label(tag);
+                if ui.small_button("✖").clicked() {
+                    removed = Some(tag.clone());
+                }
+            });
+        }
+        if let Some(tag) = removed {
+            app.remove_tag(id, &tag);
+        }
+
+        let input = app.tag_inputs.entry(id).or_default();
+        let response = ui.add(
+            egui::TextEdit::singleline(input)
+                .hint_text("Add tag")
+                .desired_width(80.0),
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let name = std::mem::take(input);
+            app.add_tag(id, &name);
+        }
+
+        ui.add_space(10.0);
+
+        let current_folder = app.favorite_folders.get(&id).copied();
+        let current_name = current_folder
+            .and_then(|folder_id| app.folders.iter().find(|f| f.id == folder_id))
+            .map_or("No folder", |f| f.name.as_str())
+            .to_string();
+
+        let mut selected = current_folder;
+        egui::ComboBox::from_id_salt(("folder", id))
+            .selected_text(format!("📁 {}", current_name))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, "No folder");
+                for folder in &app.folders {
+                    ui.selectable_value(&mut selected, Some(folder.id), &folder.name);
+                }
+            });
+        if selected != current_folder {
+            app.set_folder(id, selected);
+        }
+    });
+
+    let has_notes = app.notes.get(&id).is_some_and(|n| !n.trim().is_empty());
+    let header = if has_notes { "📝 Notes •" } else { "📝 Notes" };
+    egui::CollapsingHeader::new(header)
+        .id_salt(("notes", id))
+        .show(ui, |ui| {
+            let notes = app.notes.entry(id).or_default();
+            let response = ui.add(
+                egui::TextEdit::multiline(notes)
+                    .hint_text("Markdown notes")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            );
+            if response.changed() {
+                app.notes_changed(id);
+            }
+            if response.lost_focus() {
+                app.save_notes(id);
+            }
+        });
+}
+
+pub fn render_article(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    let theme = app.theme;
+    let Some(view) = app.article_view.as_ref() else {
+        return;
+    };
+    let ArticleView {
+        story,
+        article,
+        error,
+        archived,
+        images,
+    } = view;
+    let url = story.story.url.clone().unwrap_or_else(|| story.hn_url());
+    let mut close = false;
+    let mut to_load = Vec::new();
+
+    egui::SidePanel::right("article")
+        .default_width(550.0)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Reader");
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("✖").clicked() {
+                        close = true;
+                    }
+
+                    if ui.button("🌐 Open in browser").clicked() {
+                        opener::open(url.as_str()).ok();
+                    }
+                });
+            });
+
+            let title = article
+                .as_ref()
+                .and_then(|a| a.title.as_ref())
+                .unwrap_or(&story.story.title);
+            ui.label(egui::RichText::new(title).strong());
+
+            if let Some(error) = error {
+                ui.separator();
+                render_api_error(ui, "Failed to load article", error);
+                return;
+            }
+
+            let Some(article) = article else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading article...");
+                });
+                return;
+            };
+
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} · {} words{}",
+                    story.domain.as_deref().unwrap_or(&article.url),
+                    article.word_count(),
+                    if *archived { " · archived copy" } else { "" }
+                ))
+                .weak(),
+            );
+            ui.add_space(5.0);
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                render_article_blocks(ui, &article.blocks, images, theme, &mut to_load);
+            });
+        });
+
+    for url in to_load {
+        app.load_image(&url);
+    }
+    if close {
+        app.close_article();
+    }
+}
+
+/// Renders extracted article content in the theme's colours. Images that
+/// have not been requested yet are added to `to_load`.
+fn render_article_blocks(
+    ui: &mut egui::Ui,
+    blocks: &[ArticleBlock],
+    images: &HashMap<String, ImageState>,
+    theme: GruvboxTheme,
+    to_load: &mut Vec<String>,
+) {
+    for block in blocks {
+        match block {
+            ArticleBlock::Heading { level, text } => {
+                let size = match level {
+                    1 => 22.0,
+                    2 => 19.0,
+                    3 => 17.0,
+                    _ => 15.0,
+                };
+                ui.add_space(6.0);
+                ui.label(
+                    egui::RichText::new(text)
+                        .size(size)
+                        .strong()
+                        .color(theme.heading_color()),
+                );
+            }
+            ArticleBlock::Paragraph(text) => {
+                ui.label(text);
+            }
+            ArticleBlock::List { ordered, items, depth } => {
+                for (i, item) in items.iter().enumerate() {
+                    let bullet = if *ordered { format!("{}.", i + 1) } else { "•".to_string() };
+                    ui.horizontal_wrapped(|ui| {
+                        ui.add_space(16.0 * f32::from(*depth));
+                        ui.label(egui::RichText::new(bullet).color(theme.heading_color()));
+                        ui.label(item);
+                    });
+                }
+            }
+            ArticleBlock::Code(code) => {
+                egui::Frame::new()
+                    .fill(theme.code_background())
+                    .inner_margin(6.0)
+                    .corner_radius(4.0)
+                    .show(ui, |ui| {
+                        egui::ScrollArea::horizontal().id_salt(code).show(ui, |ui| {
+                            ui.label(egui::RichText::new(code).monospace());
+                        });
+                    });
+            }
+            ArticleBlock::Quote(text) => {
+                egui::Frame::new()
+                    .stroke(egui::Stroke::new(2.0, theme.quote_color()))
+                    .inner_margin(egui::Margin {
+                        left: 10,
+                        right: 4,
+                        top: 4,
+                        bottom: 4,
+                    })
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(text).italics().color(theme.quote_color()));
+                    });
+            }
+            ArticleBlock::Image { src, alt } => match images.get(src) {
+                Some(ImageState::Loaded(bytes)) => {
+                    let image = egui::Image::from_bytes(format!("bytes://{}", src), bytes.clone())
+                        .max_width(ui.available_width())
+                        .corner_radius(4.0);
+                    ui.add(image).on_hover_text(alt);
+                    if !alt.is_empty() {
+                        ui.label(egui::RichText::new(alt).small().color(theme.quote_color()));
+                    }
+                }
+                Some(ImageState::Loading) => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(egui::RichText::new(alt).weak());
+                    });
+                }
+                Some(ImageState::Failed) => {
+                    let label = if alt.is_empty() { "Image" } else { alt.as_str() };
+                    ui.hyperlink_to(format!("🖼 {}", label), src);
+                }
+                None => to_load.push(src.clone()),
+            },
+        }
+        ui.add_space(6.0);
+    }
+}
+
+/// The keys bound to `action`, formatted for display.
+fn shortcut_text(ctx: &egui::Context, app: &HackerNewsApp, action: Action) -> String {
+    let keys: Vec<String> = app
+        .key_bindings()
+        .shortcuts(action)
+        .iter()
+        .map(|s| ctx.format_shortcut(s))
+        .collect();
+    keys.join("  ")
+}
+
+/// A button that performs `action`, showing its description and keys on hover.
+fn action_button(ui: &mut egui::Ui, app: &mut HackerNewsApp, label: &str, action: Action) {
+    let response = ui.button(label).on_hover_text(action_hint(ui.ctx(), app, action));
+    if response.clicked() {
+        app.perform(action);
+    }
+}
+
+/// A selectable label that performs `action`, like `action_button`.
+fn action_label(ui: &mut egui::Ui, app: &mut HackerNewsApp, selected: bool, label: &str, action: Action) {
+    let response = ui.selectable_label(selected, label).on_hover_text(action_hint(ui.ctx(), app, action));
+    if response.clicked() {
+        app.perform(action);
+    }
+}
+
+fn action_hint(ctx: &egui::Context, app: &HackerNewsApp, action: Action) -> String {
+    let keys = shortcut_text(ctx, app, action);
+    if keys.is_empty() {
+        action.description()
+    } else {
+        format!("{} ({})", action.description(), keys)
+    }
+}
+
+/// The Ctrl+K palette: fuzzy-matched actions, item ids and searches. Arrow
+/// keys move the highlight and Enter runs it.
+pub fn render_command_palette(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    if app.command_palette.is_none() {
+        return;
+    }
+    let commands = app.palette_commands();
+    let shortcuts: Vec<String> = commands
+        .iter()
+        .map(|command| match command {
+            Command::Action(action) => shortcut_text(ctx, app, *action),
+            Command::Search(_) => String::new(),
+        })
+        .collect();
+    let Some(palette) = app.command_palette.as_mut() else {
+        return;
+    };
+
+    // Taken before the text field sees them.
+    let (up, down, enter, escape) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        )
+    });
+    let last = commands.len().saturating_sub(1);
+    if up {
+        palette.selected = palette.selected.saturating_sub(1);
+    }
+    if down {
+        palette.selected = (palette.selected + 1).min(last);
+    }
+    palette.selected = palette.selected.min(last);
+
+    let mut run = enter.then(|| commands.get(palette.selected).cloned()).flatten();
+    let mut close = escape;
+
+    let window = egui::Window::new("command_palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command, an item id or a search…")
+                    .desired_width(f32::INFINITY),
+            );
+            response.request_focus();
+            if response.changed() {
+                palette.selected = 0;
+            }
+
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                for (i, (command, keys)) in commands.iter().zip(&shortcuts).enumerate() {
+                    let selected = i == palette.selected;
+                    ui.horizontal(|ui| {
+                        let label = ui.selectable_label(selected, command.title());
+                        if selected && (up || down) {
+                            label.scroll_to_me(None);
+                        }
+                        if label.clicked() {
+                            run = Some(command.clone());
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(egui::RichText::new(keys).weak().monospace());
+                        });
+                    });
+                }
+            });
+        });
+
+    let just_opened = std::mem::take(&mut palette.opened_this_frame);
+    if !just_opened && window.is_some_and(|w| w.response.clicked_elsewhere()) {
+        close = true;
+    }
+
+    if let Some(command) = run {
+        app.run_command(command);
+    } else if close {
+        app.command_palette = None;
+    }
+}
+
+/// Lists every keyboard action with its bound keys.
+pub fn render_key_help(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    if !app.show_key_help {
+        return;
+    }
+    let mut open = true;
+
+    egui::Window::new("⌨ Key bindings")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Grid::new("key_bindings").striped(true).show(ui, |ui| {
+                for &action in Action::ALL {
+                    let keys = shortcut_text(ctx, app, action);
+                    if keys.is_empty() {
+                        continue;
+                    }
+                    ui.label(action.description());
+                    ui.label(egui::RichText::new(keys).monospace().color(app.theme.highlight_color()));
+                    ui.end_row();
+                }
+            });
+            ui.add_space(5.0);
+            ui.label(egui::RichText::new("Every other action is in the command palette.").weak());
+            ui.label(egui::RichText::new("Remap keys under \"key_bindings\" in config.json.").weak());
+        });
+
+    if !open {
+        app.show_key_help = false;
+    }
+}
+
+/// The settings window, holding the kill-file rules.
+pub fn render_settings(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    if !app.show_settings {
+        return;
+    }
+    let mut open = true;
+    let mut rules = app.mute_rules().to_vec();
+    let (mut changed, mut add) = (false, false);
+    let (mut remove, mut commit) = (None, None);
+
+    egui::Window::new("⚙ Settings")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.heading("🔇 Mute rules");
+            ui.label(
+                egui::RichText::new(
+                    "Stories matching a rule are hidden from the story list unless saved. \
+                     Domains also match their subdomains; title rules are case-insensitive regular expressions.",
+                )
+                .weak(),
+            );
+            ui.add_space(5.0);
+
+            if rules.is_empty() {
+                ui.label(egui::RichText::new("No mute rules yet").weak());
+            }
+            egui::Grid::new("mute_rules").striped(true).show(ui, |ui| {
+                for (index, rule) in rules.iter().enumerate() {
+                    let mut draft = match &app.mute_rule_draft {
+                        Some((i, draft)) if *i == index => draft.clone(),
+                        _ => rule.clone(),
+                    };
+                    let kind = draft.kind;
+                    let response = mute_rule_editor(ui, index, &mut draft);
+                    // Edits apply once the field loses focus (or Enter is
+                    // pressed), so a half-typed pattern never takes effect.
+                    if response.lost_focus() || draft.kind != kind {
+                        commit = Some((index, draft));
+                    } else if response.changed() {
+                        app.mute_rule_draft = Some((index, draft));
+                    }
+                    if ui.small_button("✖").on_hover_text("Remove rule").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.add_space(5.0);
+            ui.separator();
+            ui.horizontal(|ui| {
+                let response = mute_rule_fields(ui, "new", &mut app.new_mute_rule);
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if submitted || ui.button("➕ Add").clicked() {
+                    add = true;
+                }
+            });
+            if let Some(error) = pattern_error(&app.new_mute_rule) {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+
+    if let Some((index, mut rule)) = commit {
+        rule.pattern = rule.pattern.trim().to_string();
+        if pattern_error(&rule).is_none() {
+            app.mute_rule_draft = None;
+            if rule != rules[index] {
+                rules[index] = rule;
+                changed = true;
+            }
+        } else {
+            // Keep the invalid pattern on screen with its error.
+            app.mute_rule_draft = Some((index, rule));
+        }
+    }
+    if let Some(index) = remove {
+        rules.remove(index);
+        app.mute_rule_draft = None;
+        changed = true;
+    }
+    if changed {
+        app.set_mute_rules(rules);
+    }
+    if add && pattern_error(&app.new_mute_rule).is_none() {
+        app.add_mute_rule();
+    }
+    if !open {
+        app.show_settings = false;
+    }
+}
+
+/// A row of the mute rule grid; returns the pattern field's response.
+fn mute_rule_editor(ui: &mut egui::Ui, index: usize, rule: &mut MuteRule) -> egui::Response {
+    let response = ui.horizontal(|ui| mute_rule_fields(ui, index, rule)).inner;
+    if let Some(error) = pattern_error(rule) {
+        ui.colored_label(ui.visuals().error_fg_color, "⚠").on_hover_text(error);
+    } else {
+        ui.label("");
+    }
+    response
+}
+
+/// The kind picker and pattern field of a mute rule; returns the field's
+/// response.
+fn mute_rule_fields(ui: &mut egui::Ui, id: impl std::hash::Hash, rule: &mut MuteRule) -> egui::Response {
+    egui::ComboBox::from_id_salt(("mute_kind", id))
+        .selected_text(rule.kind.display_name())
+        .show_ui(ui, |ui| {
+            for kind in MuteKind::ALL {
+                ui.selectable_value(&mut rule.kind, kind, kind.display_name());
+            }
+        });
+    let hint = match rule.kind {
+        MuteKind::Domain => "example.com",
+        MuteKind::Author => "username",
+        MuteKind::Title => r"(?:crypto|\bnft\b)",
+    };
+    ui.add(
+        egui::TextEdit::singleline(&mut rule.pattern)
+            .hint_text(hint)
+            .desired_width(200.0),
+    )
+}
+
+/// Why a title rule's pattern does not compile.
+fn pattern_error(rule: &MuteRule) -> Option<String> {
+    if rule.kind != MuteKind::Title || rule.pattern.trim().is_empty() {
+        return None;
+    }
+    mute::compile(&rule.pattern)
+        .err()
+        .map(|e| format!("Invalid pattern: {}", e))
+}
+
+/// A small line of a story's score over time.
+fn sparkline(ui: &mut egui::Ui, history: &[HistoryPoint], color: egui::Color32) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(60.0, 16.0), egui::Sense::click());
+
+    let start = history.first().map_or(0, |p| p.recorded_at);
+    let end = history.last().map_or(0, |p| p.recorded_at);
+    let min = history.iter().map(|p| p.score).min().unwrap_or(0);
+    let max = history.iter().map(|p| p.score).max().unwrap_or(0);
+    let (time_span, score_span) = ((end - start).max(1) as f32, (max - min).max(1) as f32);
+
+    let points: Vec<egui::Pos2> = history
+        .iter()
+        .map(|p| {
+            egui::pos2(
+                rect.left() + (p.recorded_at - start) as f32 / time_span * rect.width(),
+                rect.bottom() - (p.score - min) as f32 / score_span * rect.height(),
+            )
+        })
+        .collect();
+    ui.painter().add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+
+    response.on_hover_cursor(egui::CursorIcon::PointingHand)
+}
+
+/// Charts of a story's score, comment count and rank in each category
+/// over the time it has been seen.
+pub fn render_history(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    let Some(story) = app.history_view.clone() else {
+        return;
+    };
+    let history = app.history(story.story.id).to_vec();
+    let theme = app.theme;
+    let mut open = true;
+
+    egui::Window::new("📈 Story history")
+        .open(&mut open)
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(&story.story.title).strong());
+
+            let (Some(first), Some(last)) = (history.first(), history.last()) else {
+                ui.label("No history yet. It is recorded each time a category listing this story is fetched.");
+                return;
+            };
+            let best = history.iter().min_by_key(|p| p.rank).unwrap_or(first);
+            ui.label(
+                egui::RichText::new(format!(
+                    "Seen {} times since {} · score {} → {} · best rank #{} in {}",
+                    history.len(),
+                    time_ago(first.recorded_at),
+                    first.score,
+                    last.score,
+                    best.rank,
+                    best.category.display_name()
+                ))
+                .weak(),
+            );
+            ui.add_space(5.0);
+
+            // Hours relative to now, so the x axis reads the same on every chart.
+            let now = unix_now();
+            let hours = |p: &HistoryPoint| -((now.saturating_sub(p.recorded_at)) as f64) / 3600.0;
+            let chart = |id: &str| {
+                egui_plot::Plot::new(id)
+                    .height(120.0)
+                    .link_axis("history", [true, false])
+                    .allow_scroll(false)
+                    .x_axis_formatter(|mark, _| format!("{:.0}h", mark.value))
+            };
+
+            ui.label("Score");
+            chart("history_score").show(ui, |plot| {
+                let points: Vec<[f64; 2]> = history.iter().map(|p| [hours(p), p.score as f64]).collect();
+                plot.line(egui_plot::Line::new("Score", points).color(theme.highlight_color()));
+            });
+
+            ui.label("Comments");
+            chart("history_comments").show(ui, |plot| {
+                let points: Vec<[f64; 2]> = history.iter().map(|p| [hours(p), p.comments as f64]).collect();
+                plot.line(egui_plot::Line::new("Comments", points).color(theme.quote_color()));
+            });
+
+            ui.label("Rank");
+            chart("history_rank")
+                .invert_y(true)
+                .legend(egui_plot::Legend::default())
+                .show(ui, |plot| {
+                    for category in Category::ALL {
+                        let points: Vec<[f64; 2]> = history
+                            .iter()
+                            .filter(|p| p.category == category)
+                            .map(|p| [hours(p), p.rank as f64])
+                            .collect();
+                        if !points.is_empty() {
+                            plot.line(egui_plot::Line::new(category.display_name(), points));
+                        }
+                    }
+                });
+        });
+
+    if !open {
+        app.close_history();
+    }
+}
+
+/// A user's karma, account age, about text and their submissions and
+/// comments, resolved a page at a time.
+pub fn render_profile(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    let watched = app
+        .profile_view
+        .as_ref()
+        .is_some_and(|v| app.watch_list().is_watched(WatchKind::User, &v.username));
+    let Some(view) = app.profile_view.as_mut() else {
+        return;
+    };
+    let remaining = view.remaining();
+    let ProfileView {
+        username,
+        user,
+        about,
+        error,
+        items,
+        failed,
+        loading_more,
+        tab,
+        ..
+    } = view;
+    let (mut close, mut load_more, mut toggle_watch) = (false, false, false);
+    let mut retry_failed = false;
+    let mut open_item = None;
+
+    egui::SidePanel::right("profile")
+        .default_width(380.0)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(format!("👤 {}", username));
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("✖").clicked() {
+                        close = true;
+                    }
+
+                    if ui.button("🌐 Open on HN").clicked() {
+                        opener::open(format!("https://news.ycombinator.com/user?id={}", username)).ok();
+                    }
+
+                    let label = if watched { "👁 Unwatch" } else { "👁 Watch" };
+                    if ui.button(label).clicked() {
+                        toggle_watch = true;
+                    }
+                });
+            });
+
+            if let Some(error) = error {
+                ui.separator();
+                render_api_error(ui, "Failed to load profile", error);
+                return;
+            }
+
+            let Some(user) = user else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading profile...");
+                });
+                return;
+            };
+
+            ui.label(format!("{} karma · joined {}", user.karma, date(user.created)));
+            if !about.is_empty() {
+                ui.add_space(5.0);
+                render_html(ui, about);
+            }
+            ui.add_space(5.0);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(tab, ProfileTab::Submissions, "Submissions");
+                ui.selectable_value(tab, ProfileTab::Comments, "Comments");
+                ui.label(egui::RichText::new(format!("{} items in total", user.submitted.len())).weak());
+            });
+            ui.add_space(5.0);
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let shown = items.iter().filter(|item| {
+                    let is_comment = item.kind == ItemKind::Comment;
+                    !item.deleted && !item.dead && is_comment == (*tab == ProfileTab::Comments)
+                });
+
+                for item in shown {
+                    if ui.link(item.display_title()).clicked() {
+                        open_item = Some(item.id);
+                    }
+                    let details = match item.kind {
+                        ItemKind::Comment => match item.parent {
+                            Some(parent) => format!("{} · reply to item {}", time_ago(item.time), parent),
+                            None => time_ago(item.time),
+                        },
+                        _ => format!(
+                            "{} points · {} comments · {}",
+                            item.score.unwrap_or(0),
+                            item.descendants.unwrap_or(0),
+                            time_ago(item.time)
+                        ),
+                    };
+                    ui.label(egui::RichText::new(details).weak());
+                    ui.add_space(6.0);
+                }
+
+                if let Some((id, error)) = failed.first().filter(|_| !*loading_more) {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("⚠ {} items failed to load", failed.len()),
+                        )
+                        .on_hover_text(format!("Item {}: {}\n{}", id, error, error.hint()));
+                        if ui.button("🔄 Retry").clicked() {
+                            retry_failed = true;
+                        }
+                    });
+                }
+
+                if *loading_more {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Loading more...");
+                    });
+                } else if remaining > 0
+                    && ui.button(format!("⬇ Load more ({} remaining)", remaining)).clicked()
+                {
+                    load_more = true;
+                }
+            });
+        });
+
+    if let Some(id) = open_item {
+        app.open_item(id);
+    }
+    if load_more {
+        app.load_more_submissions();
+    }
+    if retry_failed {
+        app.retry_failed_submissions();
+    }
+    if let Some(username) = app.profile_view.as_ref().map(|v| v.username.clone()).filter(|_| toggle_watch) {
+        if watched {
+            app.unwatch(WatchKind::User, &username);
+        } else {
+            app.watch(WatchKind::User, &username);
+        }
+    }
+    if close {
+        app.close_profile();
+    }
+}
+
+pub fn render_comments(ctx: &egui::Context, app: &mut HackerNewsApp) {
+    let highlight = app.theme.highlight_color();
+    let Some(view) = app.comment_view.as_mut() else {
+        return;
+    };
+    let CommentView {
+        story,
+        thread,
+        error,
+        collapsed,
+        previous_visit,
+    } = view;
+    let mut close = false;
+    let (mut open_profile, mut open_item) = (None, None);
+
+    egui::SidePanel::right("comments")
+        .default_width(450.0)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Comments");
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("✖").clicked() {
+                        close = true;
+                    }
+
+                    if ui.button("🌐 Open on HN").clicked() {
+                        opener::open(story.hn_url().as_str()).ok();
+                    }
+                });
+            });
+
+            ui.label(egui::RichText::new(&story.story.title).strong());
+            if let Some(visit) = previous_visit {
+                ui.label(egui::RichText::new(format!("Last visited {}", time_ago(visit.visited_at))).weak());
+            }
+            ui.add_space(5.0);
+            ui.separator();
+
+            if let Some(error) = error {
+                render_api_error(ui, "Failed to fetch comments", error);
+                return;
+            }
+
+            let Some(thread) = thread else {
+                ui.spinner();
+                ui.label("Loading comments...");
+                return;
+            };
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if let Some(parent) = thread.root_parent {
+                    if ui.link(format!("↩ In reply to item {}", parent)).clicked() {
+                        open_item = Some(parent);
+                    }
+                }
+
+                if !thread.root_body.is_empty() {
+                    render_html(ui, &thread.root_body);
+                    ui.separator();
+                }
+
+                for option in &thread.poll_options {
+                    ui.label(format!(
+                        "▪ {} ({} points)",
+                        option.display_title(),
+                        option.score.unwrap_or(0)
+                    ));
+                }
+
+                if thread.failed > 0 {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("⚠ {} comments failed to load", thread.failed),
+                    );
+                }
+
+                if thread.comments.is_empty() {
+                    ui.label("No comments yet.");
+                }
+
+                for node in &thread.comments {
+                    render_comment_node(ui, node, 0, collapsed, previous_visit.as_ref(), highlight, &mut open_profile);
+                }
+            });
+        });
+
+    if let Some(username) = open_profile {
+        app.open_profile(&username);
+    }
+    if let Some(id) = open_item {
+        app.open_item(id);
+    }
+    if close {
+        app.close_comments();
+    }
+}
+
+fn render_comment_node(
+    ui: &mut egui::Ui,
+    node: &CommentNode,
+    depth: usize,
+    collapsed: &mut HashSet<u64>,
+    previous_visit: Option<&Visit>,
+    highlight: egui::Color32,
+    open_profile: &mut Option<String>,
+) {
+    let comment = &node.comment;
+    let is_collapsed = collapsed.contains(&comment.id);
+    let is_new = previous_visit.is_some_and(|visit| visit.is_new_comment(comment, depth == 0));
+
+    ui.horizontal(|ui| {
+        ui.add_space(depth.min(MAX_COMMENT_INDENT_DEPTH) as f32 * COMMENT_INDENT);
+
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                let toggle = if is_collapsed { "[+]" } else { "[–]" };
+                if ui.small_button(toggle).clicked() {
+                    if is_collapsed {
+                        collapsed.remove(&comment.id);
+                    } else {
+                        collapsed.insert(comment.id);
+                    }
+                }
+
+                match comment.by.as_deref() {
+                    Some(author) => {
+                        if ui.link(egui::RichText::new(author).strong()).clicked() {
+                            *open_profile = Some(author.to_string());
+                        }
+                    }
+                    None => {
+                        ui.label(egui::RichText::new("[deleted]").strong());
+                    }
+                }
+                ui.label(egui::RichText::new(time_ago(comment.time)).weak());
+
+                if is_new {
+                    ui.colored_label(highlight, "● new");
+                }
+
+                let replies = node.reply_count();
+                if is_collapsed && replies > 0 {
+                    ui.label(egui::RichText::new(format!("({} replies hidden)", replies)).weak());
+                }
+            });
+
+            if !is_collapsed {
+                if comment.deleted {
+                    ui.label(egui::RichText::new("[deleted]").weak());
+                } else if comment.dead {
+                    ui.label(egui::RichText::new("[dead]").weak());
+                } else {
+                    render_html(ui, &node.body);
+                }
+            }
+        });
+    });
+
+    ui.add_space(6.0);
+
+    if !is_collapsed {
+        for child in &node.children {
+            render_comment_node(ui, child, depth + 1, collapsed, previous_visit, highlight, open_profile);
+        }
+    }
+}
+
+/// Renders text parsed by `html::parse`: wrapped paragraphs with italics and
+/// links, and code blocks on the theme's code background.
+fn render_html(ui: &mut egui::Ui, blocks: &[Block]) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+
+                    for span in spans {
+                        let mut text = egui::RichText::new(&span.text);
+                        if span.italic {
+                            text = text.italics();
+                        }
+
+                        match span.link {
+                            Some(ref url) => {
+                                ui.hyperlink_to(text, url);
+                            }
+                            None => {
+                                ui.label(text);
+                            }
+                        }
+                    }
+                });
+            }
+            Block::Code(code) => {
+                egui::Frame::new()
+                    .fill(ui.visuals().code_bg_color)
+                    .inner_margin(6.0)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(code).monospace());
+                    });
+            }
+        }
+        ui.add_space(4.0);
+    }
 }
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+/// A Hacker News account as returned by the `/user/{id}.json` endpoint.
+/// The id is left out; it is the name the account was looked up by.
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    /// When the account was created, as a unix timestamp.
+    #[serde(default)]
+    pub created: u64,
+    #[serde(default)]
+    pub karma: i64,
+    /// Self-description in the same HTML subset as item text.
+    #[serde(default)]
+    pub about: Option<String>,
+    /// Ids of the user's stories, comments, polls and jobs, newest first.
+    #[serde(default)]
+    pub submitted: Vec<u64>,
+}
@@ -0,0 +1,169 @@
+//! Watched authors and domains. Stories by a watched author or from a
+//! watched domain get a coloured badge on their card and are collected in
+//! the Watching view.
+
+use crate::story::{domain_matches, normalize_domain, StoryDisplay};
+use serde::{Deserialize, Serialize};
+
+/// Badge colours, drawn from the theme's accent colours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WatchColor {
+    #[default]
+    Yellow,
+    Green,
+    Aqua,
+    Blue,
+    Purple,
+    Red,
+    Orange,
+}
+
+impl WatchColor {
+    pub const ALL: [WatchColor; 7] = [
+        WatchColor::Yellow,
+        WatchColor::Green,
+        WatchColor::Aqua,
+        WatchColor::Blue,
+        WatchColor::Purple,
+        WatchColor::Red,
+        WatchColor::Orange,
+    ];
+
+    /// The colour after this one, wrapping around.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|c| c == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchRule {
+    /// A username, or a domain that also matches its subdomains.
+    pub name: String,
+    #[serde(default)]
+    pub color: WatchColor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    User,
+    Domain,
+}
+
+/// A rule a story matched, for drawing its badge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchMatch {
+    pub kind: WatchKind,
+    pub name: String,
+    pub color: WatchColor,
+}
+
+impl WatchMatch {
+    pub fn hover_text(&self) -> String {
+        match self.kind {
+            WatchKind::User => format!("Watched author {}", self.name),
+            WatchKind::Domain => format!("Watched domain {}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchList {
+    pub users: Vec<WatchRule>,
+    pub domains: Vec<WatchRule>,
+}
+
+impl WatchList {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.domains.is_empty()
+    }
+
+    pub fn rules(&self, kind: WatchKind) -> &[WatchRule] {
+        match kind {
+            WatchKind::User => &self.users,
+            WatchKind::Domain => &self.domains,
+        }
+    }
+
+    fn rules_mut(&mut self, kind: WatchKind) -> &mut Vec<WatchRule> {
+        match kind {
+            WatchKind::User => &mut self.users,
+            WatchKind::Domain => &mut self.domains,
+        }
+    }
+
+    pub fn is_watched(&self, kind: WatchKind, name: &str) -> bool {
+        let name = normalize(kind, name);
+        self.rules(kind)
+            .iter()
+            .any(|rule| normalize(kind, &rule.name) == name)
+    }
+
+    /// Adds a rule unless `name` is blank or already watched. New users
+    /// default to yellow and new domains to aqua.
+    pub fn add(&mut self, kind: WatchKind, name: &str) -> bool {
+        let name = normalize(kind, name);
+        if name.is_empty() || self.is_watched(kind, &name) {
+            return false;
+        }
+
+        let color = match kind {
+            WatchKind::User => WatchColor::Yellow,
+            WatchKind::Domain => WatchColor::Aqua,
+        };
+        self.rules_mut(kind).push(WatchRule { name, color });
+        true
+    }
+
+    pub fn remove(&mut self, kind: WatchKind, name: &str) {
+        let name = normalize(kind, name);
+        self.rules_mut(kind)
+            .retain(|rule| normalize(kind, &rule.name) != name);
+    }
+
+    pub fn cycle_color(&mut self, kind: WatchKind, index: usize) {
+        if let Some(rule) = self.rules_mut(kind).get_mut(index) {
+            rule.color = rule.color.next();
+        }
+    }
+
+    /// The rules `story` matches: its author, then its domain.
+    pub fn matches(&self, story: &StoryDisplay) -> Vec<WatchMatch> {
+        let by = story.story.by.to_lowercase();
+        let user = self
+            .users
+            .iter()
+            .find(|rule| normalize(WatchKind::User, &rule.name) == by)
+            .map(|rule| WatchMatch {
+                kind: WatchKind::User,
+                name: story.story.by.clone(),
+                color: rule.color,
+            });
+
+        let domain = story.domain.as_deref().and_then(|host| {
+            self.domains
+                .iter()
+                .find(|rule| domain_matches(host, &rule.name))
+                .map(|rule| WatchMatch {
+                    kind: WatchKind::Domain,
+                    name: rule.name.clone(),
+                    color: rule.color,
+                })
+        });
+
+        user.into_iter().chain(domain).collect()
+    }
+
+    pub fn is_match(&self, story: &StoryDisplay) -> bool {
+        !self.matches(story).is_empty()
+    }
+}
+
+/// Usernames compare case-insensitively; domains are normalized.
+fn normalize(kind: WatchKind, name: &str) -> String {
+    match kind {
+        WatchKind::User => name.trim().to_lowercase(),
+        WatchKind::Domain => normalize_domain(name),
+    }
+}